@@ -1,5 +1,7 @@
 use starframe as sf;
 
+use crate::player::{PlayerAnim, PlayerTint};
+
 pub struct Assets {
     // meshes will come from gltf eventually,
     // but it might still be nice to have them in this struct
@@ -9,16 +11,35 @@ pub struct Assets {
     pub cloud_mesh: sf::MeshId,
     pub player_collider: sf::Collider,
     pub player_mesh: sf::MeshId,
-    // separate mesh with a different color for when double jump is spent
-    pub player_mesh_doublejumped: sf::MeshId,
+    // one mesh per animation frame, in a separate color for when double jump is spent
+    // and while dashing. looked up with `player_anim_mesh`
+    player_anim_meshes: [[Vec<sf::MeshId>; PlayerTint::ALL.len()]; PlayerAnim::ALL.len()],
+    pub player_visor_mesh: sf::MeshId,
     pub bullet_mesh: sf::MeshId,
+    pub pickup_mesh: sf::MeshId,
     pub background_mesh: sf::MeshId,
     pub spike_roller_mesh: sf::MeshId,
     pub bomb_mesh: sf::MeshId,
+    pub walker_collider: sf::Collider,
+    pub walker_mesh: sf::MeshId,
+    pub turret_mesh: sf::MeshId,
+    pub enemy_projectile_mesh: sf::MeshId,
+    pub combo_pip_mesh: sf::MeshId,
+    pub score_segment_mesh: sf::MeshId,
+    pub score_segment_vertical_mesh: sf::MeshId,
+    pub boss_mesh: sf::MeshId,
+    pub boss_health_pip_mesh: sf::MeshId,
+    // white versions of the enemy meshes, shown for a moment when an enemy takes damage
+    pub bomb_flash_mesh: sf::MeshId,
+    pub walker_flash_mesh: sf::MeshId,
+    pub turret_flash_mesh: sf::MeshId,
+    pub boss_flash_mesh: sf::MeshId,
+    pub enemy_debris_mesh: sf::MeshId,
     pub lantern_mesh: sf::MeshId,
     pub you_win_mesh: sf::MeshId,
     pub game_over_mesh: sf::MeshId,
     pub barbut_mesh: sf::MeshId,
+    pub letterbox_mesh: sf::MeshId,
 }
 
 impl Assets {
@@ -56,11 +77,6 @@ impl Assets {
         game.graphics
             .set_mesh_material(player_mesh, player_material);
 
-        let player_mesh_doublejumped = game.graphics.create_mesh(sf::MeshParams {
-            name: Some("player"),
-            data: sf::MeshData::from(player_collider),
-            ..Default::default()
-        });
         let player_material_doublejumped = game.graphics.create_material(sf::MaterialParams {
             name: Some("player doublejump spent"),
             base_color: Some([0.700, 0.368, 0.161, 1.]),
@@ -71,8 +87,56 @@ impl Assets {
             }),
             ..Default::default()
         });
+
+        let player_material_dashing = game.graphics.create_material(sf::MaterialParams {
+            name: Some("player dashing"),
+            base_color: Some([0.851, 0.910, 0.957, 1.]),
+            emissive_color: Some([0.851, 0.910, 0.957, 1.]),
+            attenuation: Some(sf::AttenuationParams {
+                color: [0.851, 0.910, 0.957],
+                distance: 0.25,
+            }),
+            ..Default::default()
+        });
+
+        // materials in the order of `PlayerTint::ALL`
+        let player_tint_materials = [
+            player_material,
+            player_material_doublejumped,
+            player_material_dashing,
+        ];
+        let player_anim_meshes = PlayerAnim::ALL.map(|anim| {
+            player_tint_materials.map(|material| {
+                anim.clip()
+                    .shapes
+                    .iter()
+                    .map(|&[width, height]| {
+                        let mesh = game.graphics.create_mesh(sf::MeshParams {
+                            name: Some("player"),
+                            data: sf::MeshData::from(sf::Collider::new_rounded_rect(
+                                width, height, 0.1,
+                            )),
+                            ..Default::default()
+                        });
+                        game.graphics.set_mesh_material(mesh, material);
+                        mesh
+                    })
+                    .collect()
+            })
+        });
+
+        let player_visor_mesh = game.graphics.create_mesh(sf::MeshParams {
+            name: Some("player_visor"),
+            data: sf::MeshData::from(sf::Collider::new_rounded_rect(0.25, 0.12, 0.04)),
+            ..Default::default()
+        });
+        let player_visor_material = game.graphics.create_material(sf::MaterialParams {
+            name: Some("player_visor"),
+            base_color: Some([0.102, 0.118, 0.149, 1.]),
+            ..Default::default()
+        });
         game.graphics
-            .set_mesh_material(player_mesh_doublejumped, player_material_doublejumped);
+            .set_mesh_material(player_visor_mesh, player_visor_material);
 
         let bullet_mesh = game.graphics.create_mesh(sf::MeshParams {
             name: Some("bullet"),
@@ -92,6 +156,24 @@ impl Assets {
         game.graphics
             .set_mesh_material(bullet_mesh, bullet_material);
 
+        let pickup_mesh = game.graphics.create_mesh(sf::MeshParams {
+            name: Some("pickup"),
+            data: sf::MeshData::from(sf::Collider::new_circle(0.3)),
+            ..Default::default()
+        });
+        let pickup_material = game.graphics.create_material(sf::MaterialParams {
+            name: Some("pickup"),
+            base_color: Some([0.420, 0.780, 0.910, 1.]),
+            emissive_color: Some([0.420, 0.780, 0.910, 1.]),
+            attenuation: Some(sf::AttenuationParams {
+                color: [0.420, 0.780, 0.910],
+                distance: 0.25,
+            }),
+            ..Default::default()
+        });
+        game.graphics
+            .set_mesh_material(pickup_mesh, pickup_material);
+
         let bomb_mesh = game.graphics.create_mesh(sf::MeshParams {
             name: Some("enemy"),
             data: sf::MeshData::from(sf::Collider::new_circle(0.4)),
@@ -109,6 +191,184 @@ impl Assets {
         });
         game.graphics.set_mesh_material(bomb_mesh, bomb_material);
 
+        let walker_collider = sf::Collider::new_rect(
+            crate::enemy::walker::WALKER_WIDTH,
+            crate::enemy::walker::WALKER_HEIGHT,
+        )
+        .with_material(sf::PhysicsMaterial::frictionless_unelastic());
+        let walker_mesh = game.graphics.create_mesh(sf::MeshParams {
+            name: Some("walker"),
+            data: sf::MeshData::from(walker_collider),
+            ..Default::default()
+        });
+        let walker_material = game.graphics.create_material(sf::MaterialParams {
+            name: Some("walker"),
+            base_color: Some([0.820, 0.420, 0.180, 1.]),
+            emissive_color: Some([0.820, 0.420, 0.180, 1.]),
+            attenuation: Some(sf::AttenuationParams {
+                color: [0.820, 0.420, 0.180],
+                distance: 0.25,
+            }),
+            ..Default::default()
+        });
+        game.graphics
+            .set_mesh_material(walker_mesh, walker_material);
+
+        let turret_mesh = game.graphics.create_mesh(sf::MeshParams {
+            name: Some("turret"),
+            data: sf::MeshData::from(sf::Collider::new_square(0.8)),
+            ..Default::default()
+        });
+        let turret_material = game.graphics.create_material(sf::MaterialParams {
+            name: Some("turret"),
+            base_color: Some([0.620, 0.180, 0.380, 1.]),
+            emissive_color: Some([0.620, 0.180, 0.380, 1.]),
+            attenuation: Some(sf::AttenuationParams {
+                color: [0.620, 0.180, 0.380],
+                distance: 0.25,
+            }),
+            ..Default::default()
+        });
+        game.graphics
+            .set_mesh_material(turret_mesh, turret_material);
+
+        let enemy_projectile_mesh = game.graphics.create_mesh(sf::MeshParams {
+            name: Some("enemy_projectile"),
+            data: sf::MeshData::from(sf::Collider::new_circle(0.2)),
+            ..Default::default()
+        });
+        let enemy_projectile_material = game.graphics.create_material(sf::MaterialParams {
+            name: Some("enemy_projectile"),
+            base_color: Some([0.960, 0.380, 0.620, 1.]),
+            emissive_color: Some([0.960, 0.380, 0.620, 1.]),
+            attenuation: Some(sf::AttenuationParams {
+                color: [0.960, 0.380, 0.620],
+                distance: 0.25,
+            }),
+            ..Default::default()
+        });
+        game.graphics
+            .set_mesh_material(enemy_projectile_mesh, enemy_projectile_material);
+
+        let combo_pip_mesh = game.graphics.create_mesh(sf::MeshParams {
+            name: Some("combo_pip"),
+            data: sf::MeshData::from(sf::Collider::new_circle(0.1)),
+            ..Default::default()
+        });
+        let combo_pip_material = game.graphics.create_material(sf::MaterialParams {
+            name: Some("combo_pip"),
+            base_color: Some([0.990, 0.850, 0.310, 1.]),
+            emissive_color: Some([0.990, 0.850, 0.310, 1.]),
+            ..Default::default()
+        });
+        game.graphics
+            .set_mesh_material(combo_pip_mesh, combo_pip_material);
+
+        let score_segment_material = game.graphics.create_material(sf::MaterialParams {
+            name: Some("score_segment"),
+            base_color: Some([0.990, 0.850, 0.310, 1.]),
+            emissive_color: Some([0.990, 0.850, 0.310, 1.]),
+            ..Default::default()
+        });
+        let score_segment_mesh = game.graphics.create_mesh(sf::MeshParams {
+            name: Some("score_segment"),
+            data: sf::MeshData::from(sf::Collider::new_rect(
+                crate::score::SEGMENT_LEN as f64,
+                crate::score::SEGMENT_THICKNESS as f64,
+            )),
+            ..Default::default()
+        });
+        game.graphics
+            .set_mesh_material(score_segment_mesh, score_segment_material);
+        let score_segment_vertical_mesh = game.graphics.create_mesh(sf::MeshParams {
+            name: Some("score_segment"),
+            data: sf::MeshData::from(sf::Collider::new_rect(
+                crate::score::SEGMENT_THICKNESS as f64,
+                crate::score::SEGMENT_LEN as f64,
+            )),
+            ..Default::default()
+        });
+        game.graphics
+            .set_mesh_material(score_segment_vertical_mesh, score_segment_material);
+
+        let boss_mesh = game.graphics.create_mesh(sf::MeshParams {
+            name: Some("boss"),
+            data: sf::MeshData::from(sf::Collider::new_circle(crate::enemy::boss::BOSS_RADIUS)),
+            ..Default::default()
+        });
+        let boss_material = game.graphics.create_material(sf::MaterialParams {
+            name: Some("boss"),
+            base_color: Some([0.560, 0.120, 0.160, 1.]),
+            emissive_color: Some([0.560, 0.120, 0.160, 1.]),
+            attenuation: Some(sf::AttenuationParams {
+                color: [0.560, 0.120, 0.160],
+                distance: 0.5,
+            }),
+            ..Default::default()
+        });
+        game.graphics.set_mesh_material(boss_mesh, boss_material);
+
+        let boss_health_pip_mesh = game.graphics.create_mesh(sf::MeshParams {
+            name: Some("boss_health_pip"),
+            data: sf::MeshData::from(sf::Collider::new_rect(0.4, 0.3)),
+            ..Default::default()
+        });
+        let boss_health_pip_material = game.graphics.create_material(sf::MaterialParams {
+            name: Some("boss_health_pip"),
+            base_color: Some([0.930, 0.220, 0.220, 1.]),
+            emissive_color: Some([0.930, 0.220, 0.220, 1.]),
+            ..Default::default()
+        });
+        game.graphics
+            .set_mesh_material(boss_health_pip_mesh, boss_health_pip_material);
+
+        // sf note: flashing is done by swapping to a copy of the mesh with a white material.
+        // it would be nice to be able to override the material per entity instead
+        let enemy_flash_material = game.graphics.create_material(sf::MaterialParams {
+            name: Some("enemy_flash"),
+            base_color: Some([1., 1., 1., 1.]),
+            emissive_color: Some([1., 1., 1., 1.]),
+            attenuation: Some(sf::AttenuationParams {
+                color: [1., 1., 1.],
+                distance: 0.25,
+            }),
+            ..Default::default()
+        });
+        let mut create_flash_mesh = |data: sf::MeshData| {
+            let mesh = game.graphics.create_mesh(sf::MeshParams {
+                name: Some("enemy_flash"),
+                data,
+                ..Default::default()
+            });
+            game.graphics.set_mesh_material(mesh, enemy_flash_material);
+            mesh
+        };
+        let bomb_flash_mesh = create_flash_mesh(sf::MeshData::from(sf::Collider::new_circle(0.4)));
+        let walker_flash_mesh = create_flash_mesh(sf::MeshData::from(walker_collider));
+        let turret_flash_mesh =
+            create_flash_mesh(sf::MeshData::from(sf::Collider::new_square(0.8)));
+        let boss_flash_mesh = create_flash_mesh(sf::MeshData::from(sf::Collider::new_circle(
+            crate::enemy::boss::BOSS_RADIUS,
+        )));
+
+        let enemy_debris_mesh = game.graphics.create_mesh(sf::MeshParams {
+            name: Some("enemy_debris"),
+            data: sf::MeshData::from(sf::Collider::new_circle(0.1)),
+            ..Default::default()
+        });
+        let enemy_debris_material = game.graphics.create_material(sf::MaterialParams {
+            name: Some("enemy_debris"),
+            base_color: Some([0.930, 0.298, 0.140, 1.]),
+            emissive_color: Some([0.930, 0.298, 0.140, 1.]),
+            attenuation: Some(sf::AttenuationParams {
+                color: [0.930, 0.298, 0.140],
+                distance: 0.25,
+            }),
+            ..Default::default()
+        });
+        game.graphics
+            .set_mesh_material(enemy_debris_mesh, enemy_debris_material);
+
         let background_mesh = game.graphics.create_mesh(sf::MeshParams {
             name: Some("wall"),
             data: sf::MeshData::from(sf::Collider::new_rect(
@@ -163,21 +423,56 @@ impl Assets {
         game.graphics
             .set_mesh_material(lantern_mesh, lantern_material);
 
+        let letterbox_mesh = game.graphics.create_mesh(sf::MeshParams {
+            name: Some("letterbox"),
+            data: sf::MeshData::from(sf::Collider::new_square(crate::viewport::BAR_SIZE as f64)),
+            ..Default::default()
+        });
+        let letterbox_material = game.graphics.create_material(sf::MaterialParams {
+            name: Some("letterbox"),
+            base_color: Some([0., 0., 0., 1.]),
+            ..Default::default()
+        });
+        game.graphics
+            .set_mesh_material(letterbox_mesh, letterbox_material);
+
         Self {
             block_wood_mesh,
             block_stone_mesh,
             cloud_mesh,
             player_collider,
             player_mesh,
-            player_mesh_doublejumped,
+            player_anim_meshes,
+            player_visor_mesh,
             bullet_mesh,
+            pickup_mesh,
             background_mesh,
             spike_roller_mesh,
             bomb_mesh,
+            walker_collider,
+            walker_mesh,
+            turret_mesh,
+            enemy_projectile_mesh,
+            combo_pip_mesh,
+            score_segment_mesh,
+            score_segment_vertical_mesh,
+            boss_mesh,
+            boss_health_pip_mesh,
+            bomb_flash_mesh,
+            walker_flash_mesh,
+            turret_flash_mesh,
+            boss_flash_mesh,
+            enemy_debris_mesh,
             lantern_mesh,
             you_win_mesh,
             game_over_mesh,
             barbut_mesh,
+            letterbox_mesh,
         }
     }
+
+    /// Mesh for a frame of a player animation in the given color.
+    pub fn player_anim_mesh(&self, anim: PlayerAnim, tint: PlayerTint, frame: usize) -> sf::MeshId {
+        self.player_anim_meshes[anim as usize][tint as usize][frame]
+    }
 }
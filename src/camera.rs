@@ -0,0 +1,128 @@
+use rand::Rng;
+use starframe as sf;
+
+use crate::player::{MovementState, PlayerState};
+
+/// Height of the camera at the start of the level.
+const START_HEIGHT: f32 = 3.;
+/// Time in seconds for the camera to cover about two thirds of the distance to its target.
+const DAMPING_TIME: f32 = 0.15;
+/// How far above the player the camera looks when moving upwards,
+/// as seconds of the player's current vertical speed.
+const LOOKAHEAD_TIME: f32 = 0.2;
+const MAX_LOOKAHEAD: f32 = 2.5;
+const DEFAULT_AUTO_SCROLL_SPEED: f32 = 1.5;
+
+#[derive(Clone, Copy, Debug)]
+pub enum CameraMode {
+    /// follow the player upwards, never moving back down
+    Follow,
+    /// scroll upwards at a constant speed for challenge runs,
+    /// only going faster if the player gets ahead of it
+    AutoScroll { speed: f32 },
+}
+
+/// Moves the camera up the tower and handles screen shake.
+pub struct CameraController {
+    pub mode: CameraMode,
+    // current height of the camera without shake applied
+    height: f32,
+    // height the camera is moving towards.
+    // the camera never moves down so this only ever increases
+    target_height: f32,
+    shake_frames_left: u32,
+    shake_frames_total: u32,
+    shake_amplitude: f32,
+}
+
+impl CameraController {
+    pub fn new(mode: CameraMode) -> Self {
+        Self {
+            mode,
+            height: START_HEIGHT,
+            target_height: START_HEIGHT,
+            shake_frames_left: 0,
+            shake_frames_total: 0,
+            shake_amplitude: 0.,
+        }
+    }
+
+    /// Move back to the start of the level, keeping the current mode.
+    pub fn reset(&mut self, camera: &mut sf::Camera) {
+        *self = Self::new(self.mode);
+        camera.pose.translation.y = self.height;
+    }
+
+    /// Height of the camera, not counting screen shake.
+    pub fn height(&self) -> f32 {
+        self.height
+    }
+
+    pub fn toggle_auto_scroll(&mut self) {
+        self.mode = match self.mode {
+            CameraMode::Follow => CameraMode::AutoScroll {
+                speed: DEFAULT_AUTO_SCROLL_SPEED,
+            },
+            CameraMode::AutoScroll { .. } => CameraMode::Follow,
+        };
+    }
+
+    /// Start shaking the screen, fading out over the given number of frames.
+    /// Overrides any shake already in progress.
+    pub fn shake(&mut self, amplitude: f32, frames: u32) {
+        self.shake_amplitude = amplitude;
+        self.shake_frames_left = frames;
+        self.shake_frames_total = frames;
+    }
+
+    /// Move the camera for this tick. Call once per update after the physics step.
+    pub fn tick(&mut self, game: &mut sf::Game, player: &PlayerState, camera: &mut sf::Camera) {
+        self.shake_frames_left = self.shake_frames_left.saturating_sub(1);
+        let dt = game.dt_fixed as f32;
+
+        // stop following once the player is gone
+        if player.state() != MovementState::Dead {
+            if let Ok((pose, body_key)) = game
+                .world
+                .query_one_mut::<(&sf::Pose, &sf::BodyKey)>(player.entity)
+            {
+                let player_yvel = game
+                    .physics
+                    .entity_set
+                    .get_body(*body_key)
+                    .map(|body| body.velocity.linear.y as f32)
+                    .unwrap_or(0.);
+                // look ahead when going up so that the player can see where they're jumping
+                let lookahead = (player_yvel * LOOKAHEAD_TIME).clamp(0., MAX_LOOKAHEAD);
+                let player_target = pose.translation.y + lookahead;
+                if player_target > self.target_height {
+                    self.target_height = player_target;
+                }
+            }
+
+            if let CameraMode::AutoScroll { speed } = self.mode {
+                self.target_height = self.target_height.max(self.height) + speed * dt;
+            }
+        }
+
+        let approach = 1. - (-dt / DAMPING_TIME).exp();
+        self.height += (self.target_height - self.height) * approach;
+        camera.pose.translation.y = self.height;
+    }
+
+    /// Random offset to apply to the camera when drawing.
+    /// This isn't applied to the camera directly
+    /// so that it doesn't affect anything that follows the camera.
+    pub fn shake_offset(&self) -> sf::Vec2 {
+        if self.shake_frames_left == 0 {
+            return sf::Vec2::zero();
+        }
+        let amplitude =
+            self.shake_amplitude * self.shake_frames_left as f32 / self.shake_frames_total as f32;
+        let mut rng = rand::thread_rng();
+        sf::Vec2::new(
+            rng.gen_range(-amplitude..=amplitude),
+            rng.gen_range(-amplitude..=amplitude),
+        )
+    }
+}
@@ -1,81 +1,249 @@
-use starframe::{self as sf, math::ConvertPrecision};
+use rand::Rng;
+use starframe as sf;
 
-use crate::{player::PlayerState, Assets};
+use crate::{player::PlayerState, spike_roller::SpikeRoller, Assets};
 
-const BAT_CHASE_SPEED: f64 = 3.;
-const BAT_SPOT_RANGE: f64 = 8.;
+pub mod bat;
+pub mod boss;
+pub mod turret;
+pub mod walker;
 
+/// Every enemy type in the game.
+/// Adding a type here makes it spawnable from level patterns using its symbol, if it has one.
+pub static ENEMY_TYPES: &[&EnemyType] = &[&bat::BAT, &walker::WALKER, &turret::TURRET, &boss::BOSS];
+
+/// Damage dealt to an enemy by the player jumping on it or running into it.
+pub const CONTACT_DAMAGE: f32 = 1.;
+/// Speed added to an enemy in the direction of a bullet that hits it.
+const BULLET_KNOCKBACK_SPEED: f64 = 4.;
+/// Frames an enemy is stunned for after being knocked back,
+/// during which it doesn't control its own movement.
+const KNOCKBACK_FRAMES: u32 = 12;
+const FLASH_FRAMES: u32 = 6;
+const DEBRIS_COUNT: usize = 6;
+const DEBRIS_SPEED: f64 = 5.;
+const DEBRIS_FRAMES: u32 = 30;
+/// Distance above the spike roller at which fleeing enemies start flying up.
+const FLEE_DISTANCE: f32 = 3.;
+/// Upward speed fleeing enemies are pulled towards, more strongly the closer the roller is.
+const FLEE_SPEED: f64 = 6.;
+
+/// Hooks that define the behaviour of one type of enemy.
+/// Each type keeps its own state in its own components
+/// and is responsible for querying them in `tick`.
+#[derive(Debug)]
+pub struct EnemyType {
+    /// character marking possible spawn locations in level patterns,
+    /// `None` for enemies that are only spawned by code
+    pub symbol: Option<char>,
+    /// spawn an enemy of this type at the given position.
+    /// the entity must have an `Enemy` component for hits to reach `on_hit`
+    pub spawn: fn(&mut sf::Game, &Assets, sf::Vec2) -> sf::hecs::Entity,
+    /// update every enemy of this type, called once per tick
+    pub tick: fn(&mut sf::Game, &Assets, &PlayerState),
+    /// react to being hit by the player or a bullet
+    pub on_hit: fn(&mut sf::Game, &Assets, sf::hecs::Entity, EnemyHit),
+    pub roller_reaction: RollerReaction,
+}
+
+/// How an enemy type deals with the spike roller coming up from below.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum RollerReaction {
+    /// fly upwards when the roller gets close.
+    /// only works for enemies with a body,
+    /// and they're still destroyed if the roller catches them
+    Flee,
+    /// get destroyed on contact, giving the player some points
+    Shred { score: u64 },
+    /// not affected by the roller at all
+    Ignore,
+}
+
+impl EnemyType {
+    pub fn from_symbol(c: char) -> Option<&'static Self> {
+        ENEMY_TYPES.iter().copied().find(|ty| ty.symbol == Some(c))
+    }
+}
+
+/// Component shared by all enemies, used to recognize them in collisions
+/// and find the type-specific hooks.
+#[derive(Clone, Copy, Debug)]
+pub struct Enemy {
+    pub ty: &'static EnemyType,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum EnemyHit {
+    Bullet {
+        damage: f32,
+        dir: sf::DVec2,
+    },
+    /// the player landed on top of the enemy
+    Stomped,
+    /// the player ran into the enemy from the side or below
+    Touched,
+}
+
+/// Hit points of an enemy.
+/// Enemies without this component die to any hit.
+#[derive(Clone, Copy, Debug)]
+pub struct Health {
+    pub hp: f32,
+    flash_frames_left: u32,
+    stun_frames_left: u32,
+    // mesh to go back to after flashing
+    mesh: sf::MeshId,
+    // same shape as `mesh` in white, shown while flashing
+    flash_mesh: sf::MeshId,
+}
+
+impl Health {
+    pub fn new(hp: f32, mesh: sf::MeshId, flash_mesh: sf::MeshId) -> Self {
+        Self {
+            hp,
+            flash_frames_left: 0,
+            stun_frames_left: 0,
+            mesh,
+            flash_mesh,
+        }
+    }
+
+    /// Whether the enemy has been knocked back recently
+    /// and shouldn't move on its own.
+    pub fn is_stunned(&self) -> bool {
+        self.stun_frames_left > 0
+    }
+}
+
+/// Bits flying off a killed enemy, removed after a short while.
 #[derive(Clone, Copy, Debug)]
-pub enum Enemy {
-    Bat { is_active: bool },
+pub struct Debris {
+    frames_left: u32,
 }
 
 impl Enemy {
-    pub fn bat() -> Self {
-        Self::Bat { is_active: false }
+    /// Update enemies of every type, their damage effects and leftover debris.
+    pub fn tick(game: &mut sf::Game, assets: &Assets, player: &PlayerState, roller: &SpikeRoller) {
+        for ty in ENEMY_TYPES {
+            (ty.tick)(game, assets, player);
+        }
+        // projectiles are shared by every enemy that shoots
+        turret::tick_projectiles(game);
+
+        // blended with the velocity set by the type's own tick
+        // so that enemies keep some control while fleeing
+        if let Some(roller_height) = roller.height(game) {
+            for (_, (enemy, pose, body_key)) in
+                game.world.query_mut::<(&Enemy, &sf::Pose, &sf::BodyKey)>()
+            {
+                let dist_to_roller = pose.translation.y - roller_height;
+                if enemy.ty.roller_reaction != RollerReaction::Flee
+                    || !(0. ..=FLEE_DISTANCE).contains(&dist_to_roller)
+                {
+                    continue;
+                }
+                let urgency = (1. - dist_to_roller / FLEE_DISTANCE) as f64;
+                if let Some(body) = game.physics.entity_set.get_body_mut(*body_key) {
+                    let missing_speed = (FLEE_SPEED - body.velocity.linear.y).max(0.);
+                    body.velocity.linear.y += missing_speed * urgency;
+                }
+            }
+        }
+
+        for (_, (health, mesh)) in game.world.query_mut::<(&mut Health, &mut sf::MeshId)>() {
+            health.stun_frames_left = health.stun_frames_left.saturating_sub(1);
+            if health.flash_frames_left > 0 {
+                health.flash_frames_left -= 1;
+                *mesh = health.flash_mesh;
+            } else {
+                *mesh = health.mesh;
+            }
+        }
+
+        let mut despawn_queue: Vec<sf::hecs::Entity> = Vec::new();
+        for (ent, (debris,)) in game.world.query_mut::<(&mut Debris,)>() {
+            debris.frames_left = debris.frames_left.saturating_sub(1);
+            if debris.frames_left == 0 {
+                despawn_queue.push(ent);
+            }
+        }
+        for ent in despawn_queue {
+            game.world.despawn(ent).ok();
+        }
     }
 
-    pub fn spawn(&self, game: &mut sf::Game, assets: &Assets, pos: sf::Vec2) {
-        let pose = sf::PoseBuilder::new().with_position(pos).build();
-        let body = sf::Body::new_particle(1.).ignore_gravity();
-        let body = game.physics.entity_set.insert_body(body);
-        let coll = sf::Collider::new_circle(0.25)
-            .with_material(sf::PhysicsMaterial::frictionless_unelastic())
-            .with_layer(crate::physics_layers::ENEMY);
-        let coll = game.physics.entity_set.attach_collider(body, coll);
-        let mesh = assets.bomb_mesh;
-
-        game.world.spawn((*self, pose, body, coll, mesh));
+    /// Pass a hit to the hook of the entity's enemy type.
+    /// Does nothing if the entity isn't an enemy.
+    pub fn hit(game: &mut sf::Game, assets: &Assets, ent: sf::hecs::Entity, hit: EnemyHit) {
+        let Ok(ty) = game.world.get::<&Enemy>(ent).map(|enemy| enemy.ty) else {
+            return;
+        };
+        (ty.on_hit)(game, assets, ent, hit);
     }
 
-    pub fn tick(game: &mut sf::Game, player: &PlayerState) {
-        let Ok((&player_pose,)) = game.world.query_one_mut::<(&sf::Pose,)>(player.entity) else {
+    /// Remove an enemy and leave a burst of debris where it was.
+    pub fn kill(game: &mut sf::Game, assets: &Assets, ent: sf::hecs::Entity) {
+        let Ok(pos) = game.world.get::<&sf::Pose>(ent).map(|p| p.translation.xy()) else {
             return;
         };
+        game.world.despawn(ent).ok();
 
-        for (_, (enemy, bat_pose, body_key)) in game
-            .world
-            .query_mut::<(&mut Self, &sf::Pose, &sf::BodyKey)>()
-        {
-            match enemy {
-                Self::Bat { is_active } => {
-                    let Some(body) = game.physics.entity_set.get_body_mut(*body_key) else {
-                        continue;
-                    };
-                    let to_player =
-                        (player_pose.translation.xy() - bat_pose.translation.xy()).to_precision();
-                    let dist_to_player = to_player.mag();
-                    let dir_to_player = to_player / dist_to_player;
-                    if *is_active {
-                        // has already seen the player, chase them
-                        body.velocity.linear = dir_to_player * BAT_CHASE_SPEED;
-                    } else if dist_to_player <= BAT_SPOT_RANGE {
-                        // check for line of sight
-                        // sf note: I'd like this to ignore clouds
-                        // but that's currently not possible,
-                        // add an API for raycasting with collision layers
-                        let Some(hit) = game.physics.raycast(sf::Ray {
-                            start: bat_pose.translation.xy().to_precision(),
-                            dir: sf::math::UnitDVec2::new_unchecked(dir_to_player),
-                            length: dist_to_player,
-                        }) else {
-                            continue;
-                        };
-
-                        if game
-                            .physics
-                            .entity_set
-                            .get_collider(hit.collider)
-                            .unwrap()
-                            .layer
-                            == crate::physics_layers::PLAYER
-                        {
-                            // player was the first thing hit -> is in line of sight
-                            *is_active = true;
-                        }
-                    }
-                }
+        let mut rng = rand::thread_rng();
+        for _ in 0..DEBRIS_COUNT {
+            let pose = sf::PoseBuilder::new().with_position(pos).build();
+            // no collider, debris is only for show
+            let body = sf::Body::new_particle(0.1);
+            let body = game.physics.entity_set.insert_body(body);
+            if let Some(body) = game.physics.entity_set.get_body_mut(body) {
+                let angle = rng.gen_range(0. ..std::f64::consts::TAU);
+                body.velocity.linear = DEBRIS_SPEED * sf::DVec2::new(angle.cos(), angle.sin());
             }
+            let debris = Debris {
+                frames_left: DEBRIS_FRAMES,
+            };
+            game.world
+                .spawn((debris, pose, body, assets.enemy_debris_mesh));
+        }
+    }
+}
+
+/// `on_hit` hook for enemies that take damage and get knocked back by bullets,
+/// dying when they run out of health.
+pub fn damage_on_hit(game: &mut sf::Game, assets: &Assets, ent: sf::hecs::Entity, hit: EnemyHit) {
+    let (damage, knockback) = match hit {
+        EnemyHit::Bullet { damage, dir } => (damage, Some(dir * BULLET_KNOCKBACK_SPEED)),
+        EnemyHit::Stomped | EnemyHit::Touched => (CONTACT_DAMAGE, None),
+    };
+    take_damage(game, assets, ent, damage, knockback);
+}
+
+/// Reduce an enemy's health, flashing it and optionally knocking it back,
+/// or kill it if it runs out.
+pub fn take_damage(
+    game: &mut sf::Game,
+    assets: &Assets,
+    ent: sf::hecs::Entity,
+    damage: f32,
+    knockback: Option<sf::DVec2>,
+) {
+    let Ok((health,)) = game.world.query_one_mut::<(&mut Health,)>(ent) else {
+        Enemy::kill(game, assets, ent);
+        return;
+    };
+    health.hp -= damage;
+    if health.hp <= 0. {
+        Enemy::kill(game, assets, ent);
+        return;
+    }
+    health.flash_frames_left = FLASH_FRAMES;
+
+    let Some(knockback) = knockback else {
+        return;
+    };
+    health.stun_frames_left = KNOCKBACK_FRAMES;
+    if let Ok((body_key,)) = game.world.query_one_mut::<(&sf::BodyKey,)>(ent) {
+        if let Some(body) = game.physics.entity_set.get_body_mut(*body_key) {
+            body.velocity.linear += knockback;
         }
     }
 }
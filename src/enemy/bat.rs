@@ -0,0 +1,199 @@
+use rand::Rng;
+use starframe::{self as sf, math::ConvertPrecision};
+
+use super::{Enemy, EnemyHit, EnemyType, Health, RollerReaction};
+use crate::{physics_query, player::PlayerState, Assets};
+
+const BAT_CHASE_SPEED: f64 = 3.;
+const BAT_SPOT_RANGE: f64 = 8.;
+/// Distance to the player beyond which a chasing bat starts losing interest.
+const BAT_LOSE_RANGE: f64 = 12.;
+/// Frames the player needs to stay out of range for a bat to give up the chase.
+const BAT_LOSE_INTEREST_FRAMES: u32 = 120;
+const BAT_HP: f32 = 0.5;
+// bats weave side to side while chasing
+const SWOOP_AMPLITUDE: f64 = 2.;
+/// Swoops per second.
+const SWOOP_FREQUENCY: f64 = 0.8;
+// after running into the player, bats back off for a moment before attacking again
+const RETREAT_SPEED: f64 = 4.;
+const RETREAT_FRAMES: u32 = 45;
+// bats push away from each other when closer than this
+// so that they don't all end up in the same spot
+const SEPARATION_RADIUS: f64 = 1.2;
+const SEPARATION_SPEED: f64 = 2.;
+
+pub static BAT: EnemyType = EnemyType {
+    symbol: Some('b'),
+    spawn,
+    tick,
+    on_hit,
+    roller_reaction: RollerReaction::Flee,
+};
+
+/// Hovers in place until it spots the player,
+/// then swoops towards them until they get far enough away.
+#[derive(Clone, Copy, Debug)]
+pub struct Bat {
+    pub state: BatState,
+    // position in the swooping motion, randomized so that bats don't move in sync
+    swoop_phase: f64,
+    out_of_range_frames: u32,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum BatState {
+    /// hovering in place, looking for the player
+    Idle,
+    Chase,
+    /// flying away from the player after touching them
+    Retreat {
+        frames_left: u32,
+    },
+}
+
+fn spawn(game: &mut sf::Game, assets: &Assets, pos: sf::Vec2) -> sf::hecs::Entity {
+    let pose = sf::PoseBuilder::new().with_position(pos).build();
+    let body = sf::Body::new_particle(1.).ignore_gravity();
+    let body = game.physics.entity_set.insert_body(body);
+    let coll = sf::Collider::new_circle(0.25)
+        .with_material(sf::PhysicsMaterial::frictionless_unelastic())
+        .with_layer(crate::physics_layers::ENEMY);
+    let coll = game.physics.entity_set.attach_collider(body, coll);
+    let mesh = assets.bomb_mesh;
+
+    let bat = Bat {
+        state: BatState::Idle,
+        swoop_phase: rand::thread_rng().gen_range(0. ..std::f64::consts::TAU),
+        out_of_range_frames: 0,
+    };
+    game.world.spawn((
+        Enemy { ty: &BAT },
+        bat,
+        Health::new(BAT_HP, mesh, assets.bomb_flash_mesh),
+        pose,
+        body,
+        coll,
+        mesh,
+    ))
+}
+
+fn tick(game: &mut sf::Game, _assets: &Assets, player: &PlayerState) {
+    let Ok((&player_pose,)) = game.world.query_one_mut::<(&sf::Pose,)>(player.entity) else {
+        return;
+    };
+
+    // gather positions first for separation so that we don't need nested queries
+    let bat_positions: Vec<(sf::hecs::Entity, sf::DVec2)> = game
+        .world
+        .query_mut::<(&Bat, &sf::Pose)>()
+        .into_iter()
+        .map(|(ent, (_, pose))| (ent, pose.translation.xy().to_precision()))
+        .collect();
+
+    for (ent, (bat, health, bat_pose, body_key)) in
+        game.world
+            .query_mut::<(&mut Bat, &Health, &sf::Pose, &sf::BodyKey)>()
+    {
+        // let knockback play out
+        if health.is_stunned() {
+            continue;
+        }
+        let Some(body) = game.physics.entity_set.get_body_mut(*body_key) else {
+            continue;
+        };
+        let bat_pos: sf::DVec2 = bat_pose.translation.xy().to_precision();
+        let to_player = player_pose.translation.xy().to_precision() - bat_pos;
+        let dist_to_player = to_player.mag();
+        let dir_to_player = to_player / dist_to_player;
+
+        let mut velocity = match bat.state {
+            BatState::Idle => sf::DVec2::zero(),
+            BatState::Chase => {
+                bat.swoop_phase += SWOOP_FREQUENCY * std::f64::consts::TAU * game.dt_fixed;
+                let sideways = sf::DVec2::new(-dir_to_player.y, dir_to_player.x);
+                dir_to_player * BAT_CHASE_SPEED + sideways * SWOOP_AMPLITUDE * bat.swoop_phase.sin()
+            }
+            BatState::Retreat { .. } => -dir_to_player * RETREAT_SPEED,
+        };
+
+        if bat.state != BatState::Idle {
+            for &(other_ent, other_pos) in &bat_positions {
+                if other_ent == ent {
+                    continue;
+                }
+                let away = bat_pos - other_pos;
+                let dist = away.mag();
+                if dist > 0. && dist < SEPARATION_RADIUS {
+                    // push harder the closer they are
+                    velocity += away / dist * SEPARATION_SPEED * (1. - dist / SEPARATION_RADIUS);
+                }
+            }
+        }
+        body.velocity.linear = velocity;
+
+        match bat.state {
+            BatState::Idle => {
+                if dist_to_player > BAT_SPOT_RANGE {
+                    continue;
+                }
+                // check for line of sight
+                let Some(hit) = physics_query::raycast(
+                    &game.physics,
+                    sf::Ray {
+                        start: bat_pos,
+                        dir: sf::math::UnitDVec2::new_unchecked(dir_to_player),
+                        length: dist_to_player,
+                    },
+                    physics_query::LINE_OF_SIGHT,
+                ) else {
+                    continue;
+                };
+
+                if game
+                    .physics
+                    .entity_set
+                    .get_collider(hit.collider)
+                    .is_some_and(|coll| coll.layer == crate::physics_layers::PLAYER)
+                {
+                    // player was the first thing hit -> is in line of sight
+                    bat.state = BatState::Chase;
+                    bat.out_of_range_frames = 0;
+                }
+            }
+            BatState::Chase => {
+                if dist_to_player > BAT_LOSE_RANGE {
+                    bat.out_of_range_frames += 1;
+                    if bat.out_of_range_frames >= BAT_LOSE_INTEREST_FRAMES {
+                        bat.state = BatState::Idle;
+                    }
+                } else {
+                    bat.out_of_range_frames = 0;
+                }
+            }
+            BatState::Retreat { frames_left } => {
+                bat.state = if frames_left > 1 {
+                    BatState::Retreat {
+                        frames_left: frames_left - 1,
+                    }
+                } else {
+                    BatState::Chase
+                };
+            }
+        }
+    }
+}
+
+/// Bats survive running into the player from the side and back off instead,
+/// otherwise they take damage as usual.
+fn on_hit(game: &mut sf::Game, assets: &Assets, ent: sf::hecs::Entity, hit: EnemyHit) {
+    if hit == EnemyHit::Touched {
+        if let Ok(mut bat) = game.world.get::<&mut Bat>(ent) {
+            bat.state = BatState::Retreat {
+                frames_left: RETREAT_FRAMES,
+            };
+            return;
+        }
+    }
+    super::damage_on_hit(game, assets, ent, hit);
+}
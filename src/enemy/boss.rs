@@ -0,0 +1,284 @@
+use starframe::{self as sf, math::ConvertPrecision};
+
+use super::{turret::spawn_projectile, Enemy, EnemyHit, EnemyType, Health, RollerReaction};
+use crate::{
+    level::{self, LEVEL_WIDTH, VIEW_HEIGHT},
+    player::PlayerState,
+    Assets,
+};
+
+const BOSS_HP: f32 = 30.;
+pub const BOSS_RADIUS: f64 = 1.2;
+/// Height above the arena floor the boss hovers at.
+pub const HOVER_HEIGHT: f64 = 5.5;
+/// How far the boss drifts to each side of the arena while hovering.
+const HOVER_RANGE: f64 = 6.;
+/// Seconds for one sweep back and forth while hovering.
+const HOVER_PERIOD: f64 = 4.;
+// the boss steers towards its target position with a speed proportional to the distance
+const STEER_GAIN: f64 = 3.;
+const MAX_SPEED: f64 = 6.;
+// phase 1: single shots aimed at the player
+const AIMED_SHOT_INTERVAL: u32 = 80;
+// phase 2: fans of shots
+const SPREAD_SHOT_INTERVAL: u32 = 70;
+const SPREAD_SHOT_COUNT: usize = 5;
+const SPREAD_SHOT_ANGLE: f64 = 0.8;
+// phase 3: alternating between bursts in every direction and charging at the player
+const FRENZY_ATTACK_INTERVAL: u32 = 100;
+const BURST_SHOT_COUNT: usize = 10;
+const CHARGE_SPEED: f64 = 9.;
+const CHARGE_FRAMES: u32 = 40;
+// health bar shown at the top of the screen during the fight
+const HEALTH_BAR_PIPS: usize = 20;
+const HEALTH_BAR_PIP_SPACING: f32 = 0.5;
+const HEALTH_BAR_MARGIN: f32 = 0.6;
+
+/// The boss isn't placed through level patterns
+/// since there can only be one for the health bar and win condition to work.
+/// The level generator spawns it in the arena at the top of the tower.
+pub static BOSS: EnemyType = EnemyType {
+    symbol: None,
+    spawn,
+    tick,
+    on_hit,
+    roller_reaction: RollerReaction::Ignore,
+};
+
+/// Boss at the top of the tower that gets more aggressive as it loses health.
+#[derive(Clone, Copy, Debug)]
+pub struct Boss {
+    pub phase: BossPhase,
+    // height of the arena floor, the fight starts when the player reaches it
+    arena_bottom: f32,
+    attack_cooldown: u32,
+    // seconds since the fight started, drives the hovering motion
+    time: f64,
+    // direction and remaining frames of a charge in progress
+    charge: Option<(sf::DVec2, u32)>,
+    charge_next: bool,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum BossPhase {
+    /// the player hasn't reached the arena yet
+    Waiting,
+    Aimed,
+    Spread,
+    Frenzy,
+}
+
+impl BossPhase {
+    fn for_health(hp_fraction: f32) -> Self {
+        if hp_fraction > 2. / 3. {
+            Self::Aimed
+        } else if hp_fraction > 1. / 3. {
+            Self::Spread
+        } else {
+            Self::Frenzy
+        }
+    }
+}
+
+/// One segment of the boss health bar.
+#[derive(Clone, Copy, Debug)]
+pub struct HealthBarPip {
+    index: usize,
+}
+
+/// Marker entity spawned when the boss is killed by the player.
+/// Kept separate from the boss itself so that it disappearing some other way
+/// doesn't count as a win.
+#[derive(Clone, Copy, Debug)]
+pub struct BossDefeated;
+
+fn spawn(game: &mut sf::Game, assets: &Assets, pos: sf::Vec2) -> sf::hecs::Entity {
+    let pose = sf::PoseBuilder::new().with_position(pos).build();
+    let body = sf::Body::new_particle(10.).ignore_gravity();
+    let body = game.physics.entity_set.insert_body(body);
+    let coll = sf::Collider::new_circle(BOSS_RADIUS)
+        .with_material(sf::PhysicsMaterial::frictionless_unelastic())
+        .with_layer(crate::physics_layers::ENEMY);
+    let coll = game.physics.entity_set.attach_collider(body, coll);
+    let mesh = assets.boss_mesh;
+
+    for index in 0..HEALTH_BAR_PIPS {
+        let pose = sf::PoseBuilder::new()
+            .with_position(level::PARKING_POS)
+            .with_depth(-20.)
+            .build();
+        game.world
+            .spawn((HealthBarPip { index }, pose, assets.boss_health_pip_mesh));
+    }
+
+    let boss = Boss {
+        phase: BossPhase::Waiting,
+        arena_bottom: pos.y - HOVER_HEIGHT as f32,
+        attack_cooldown: AIMED_SHOT_INTERVAL,
+        time: 0.,
+        charge: None,
+        charge_next: false,
+    };
+    game.world.spawn((
+        Enemy { ty: &BOSS },
+        boss,
+        Health::new(BOSS_HP, mesh, assets.boss_flash_mesh),
+        pose,
+        body,
+        coll,
+        mesh,
+    ))
+}
+
+fn tick(game: &mut sf::Game, assets: &Assets, player: &PlayerState) {
+    let Ok((&player_pose,)) = game.world.query_one_mut::<(&sf::Pose,)>(player.entity) else {
+        return;
+    };
+    let player_pos: sf::DVec2 = player_pose.translation.xy().to_precision();
+
+    // gather shots first so that we can spawn projectiles outside of the query
+    let mut shots: Vec<(sf::Vec2, sf::DVec2)> = Vec::new();
+    for (_, (boss, health, pose, body_key)) in
+        game.world
+            .query_mut::<(&mut Boss, &Health, &sf::Pose, &sf::BodyKey)>()
+    {
+        if boss.phase == BossPhase::Waiting && player_pose.translation.y < boss.arena_bottom {
+            continue;
+        }
+        boss.phase = BossPhase::for_health(health.hp / BOSS_HP);
+        boss.time += game.dt_fixed;
+        boss.attack_cooldown = boss.attack_cooldown.saturating_sub(1);
+
+        let pos: sf::DVec2 = pose.translation.xy().to_precision();
+        let to_player = player_pos - pos;
+        let dir_to_player = if to_player.mag() > 0. {
+            to_player.normalized()
+        } else {
+            sf::DVec2::new(0., -1.)
+        };
+
+        // move
+
+        let velocity = if let Some((dir, frames_left)) = boss.charge {
+            boss.charge = (frames_left > 1).then_some((dir, frames_left - 1));
+            dir * CHARGE_SPEED
+        } else {
+            let sweep = (boss.time * std::f64::consts::TAU / HOVER_PERIOD).sin();
+            let target = sf::DVec2::new(
+                LEVEL_WIDTH as f64 / 2. + HOVER_RANGE * sweep,
+                boss.arena_bottom as f64 + HOVER_HEIGHT,
+            );
+            let steer = (target - pos) * STEER_GAIN;
+            if steer.mag() > MAX_SPEED {
+                steer.normalized() * MAX_SPEED
+            } else {
+                steer
+            }
+        };
+        if let Some(body) = game.physics.entity_set.get_body_mut(*body_key) {
+            body.velocity.linear = velocity;
+        }
+
+        // attack
+
+        if boss.attack_cooldown > 0 || boss.charge.is_some() {
+            continue;
+        }
+        let shot_pos = pose.translation.xy();
+        match boss.phase {
+            BossPhase::Waiting => {}
+            BossPhase::Aimed => {
+                shots.push((shot_pos, dir_to_player));
+                boss.attack_cooldown = AIMED_SHOT_INTERVAL;
+            }
+            BossPhase::Spread => {
+                let angle_step = SPREAD_SHOT_ANGLE / (SPREAD_SHOT_COUNT - 1) as f64;
+                for i in 0..SPREAD_SHOT_COUNT {
+                    let angle = -SPREAD_SHOT_ANGLE / 2. + i as f64 * angle_step;
+                    shots.push((shot_pos, rotate(dir_to_player, angle)));
+                }
+                boss.attack_cooldown = SPREAD_SHOT_INTERVAL;
+            }
+            BossPhase::Frenzy => {
+                if boss.charge_next {
+                    boss.charge = Some((dir_to_player, CHARGE_FRAMES));
+                } else {
+                    let angle_step = std::f64::consts::TAU / BURST_SHOT_COUNT as f64;
+                    for i in 0..BURST_SHOT_COUNT {
+                        shots.push((shot_pos, rotate(dir_to_player, i as f64 * angle_step)));
+                    }
+                }
+                boss.charge_next = !boss.charge_next;
+                boss.attack_cooldown = FRENZY_ATTACK_INTERVAL;
+            }
+        }
+    }
+
+    for (pos, dir) in shots {
+        spawn_projectile(game, pos, dir, assets.enemy_projectile_mesh);
+    }
+}
+
+fn rotate(v: sf::DVec2, angle: f64) -> sf::DVec2 {
+    let (sin, cos) = angle.sin_cos();
+    sf::DVec2::new(v.x * cos - v.y * sin, v.x * sin + v.y * cos)
+}
+
+/// The boss can't be knocked around, and running into it only hurts the player.
+/// It also can't be damaged before the fight starts.
+fn on_hit(game: &mut sf::Game, assets: &Assets, ent: sf::hecs::Entity, hit: EnemyHit) {
+    let waiting = game
+        .world
+        .get::<&Boss>(ent)
+        .is_ok_and(|boss| boss.phase == BossPhase::Waiting);
+    if waiting {
+        return;
+    }
+    let damage = match hit {
+        EnemyHit::Bullet { damage, .. } => damage,
+        EnemyHit::Stomped => super::CONTACT_DAMAGE,
+        EnemyHit::Touched => return,
+    };
+    let killed = game
+        .world
+        .get::<&Health>(ent)
+        .is_ok_and(|health| health.hp - damage <= 0.);
+    super::take_damage(game, assets, ent, damage, None);
+    if killed {
+        game.world.spawn((BossDefeated,));
+    }
+}
+
+/// Whether the boss has been killed by the player.
+pub fn is_defeated(game: &mut sf::Game) -> bool {
+    game.world
+        .query_mut::<(&BossDefeated,)>()
+        .into_iter()
+        .next()
+        .is_some()
+}
+
+/// Show the boss's health at the top of the screen while the fight is on.
+/// Call once per update after moving the camera.
+pub fn update_health_bar(game: &mut sf::Game, camera: &sf::Camera) {
+    let hp_fraction = game
+        .world
+        .query_mut::<(&Boss, &Health)>()
+        .into_iter()
+        .find(|(_, (boss, _))| boss.phase != BossPhase::Waiting)
+        .map(|(_, (_, health))| health.hp / BOSS_HP);
+    let visible_pips = hp_fraction
+        .map(|frac| (frac * HEALTH_BAR_PIPS as f32).ceil() as usize)
+        .unwrap_or(0);
+
+    let bar_y = camera.pose.translation.y + VIEW_HEIGHT / 2. - HEALTH_BAR_MARGIN;
+    let first_x = LEVEL_WIDTH / 2. - (HEALTH_BAR_PIPS - 1) as f32 * HEALTH_BAR_PIP_SPACING / 2.;
+    for (_, (pip, pose)) in game.world.query_mut::<(&HealthBarPip, &mut sf::Pose)>() {
+        if pip.index < visible_pips {
+            pose.translation.x = first_x + pip.index as f32 * HEALTH_BAR_PIP_SPACING;
+            pose.translation.y = bar_y;
+        } else {
+            level::park(pose);
+        }
+    }
+}
@@ -0,0 +1,154 @@
+use starframe::{self as sf, math::ConvertPrecision};
+
+use super::{Enemy, EnemyType, Health, RollerReaction};
+use crate::{physics_layers, physics_query, player::PlayerState, Assets};
+
+const TURRET_RANGE: f64 = 9.;
+const TURRET_HP: f32 = 1.5;
+/// Frames between shots while the player is in sight.
+const TURRET_FIRE_INTERVAL: u32 = 90;
+const PROJECTILE_SPEED: f64 = 5.;
+const PROJECTILE_RADIUS: f64 = 0.2;
+/// Frames before a projectile that hasn't hit anything disappears.
+const PROJECTILE_LIFETIME: u32 = 300;
+
+pub static TURRET: EnemyType = EnemyType {
+    symbol: Some('t'),
+    spawn,
+    tick,
+    on_hit: super::damage_on_hit,
+    roller_reaction: RollerReaction::Shred { score: 100 },
+};
+
+/// Stays in place and shoots at the player when it has line of sight.
+#[derive(Clone, Copy, Debug)]
+pub struct Turret {
+    cooldown: u32,
+}
+
+/// Projectile fired by an enemy.
+/// Breaks on walls and tiles, knocks back the player, and can be shot down.
+#[derive(Clone, Copy, Debug)]
+pub struct EnemyProjectile {
+    frames_left: u32,
+}
+
+fn spawn(game: &mut sf::Game, assets: &Assets, pos: sf::Vec2) -> sf::hecs::Entity {
+    let pose = sf::PoseBuilder::new().with_position(pos).build();
+    // no body, turrets don't move
+    let coll = sf::Collider::new_square(0.8).with_layer(physics_layers::ENEMY);
+    let coll = game.physics.entity_set.insert_collider(coll);
+    let mesh = assets.turret_mesh;
+
+    let turret = Turret {
+        cooldown: TURRET_FIRE_INTERVAL,
+    };
+    game.world.spawn((
+        Enemy { ty: &TURRET },
+        turret,
+        Health::new(TURRET_HP, mesh, assets.turret_flash_mesh),
+        pose,
+        coll,
+        mesh,
+    ))
+}
+
+fn tick(game: &mut sf::Game, assets: &Assets, player: &PlayerState) {
+    let Ok((&player_pose,)) = game.world.query_one_mut::<(&sf::Pose,)>(player.entity) else {
+        return;
+    };
+
+    // gather shots first so that we can spawn projectiles outside of the query
+    let mut shots: Vec<(sf::Vec2, sf::DVec2)> = Vec::new();
+    for (_, (turret, turret_pose)) in game.world.query_mut::<(&mut Turret, &sf::Pose)>() {
+        turret.cooldown = turret.cooldown.saturating_sub(1);
+        if turret.cooldown > 0 {
+            continue;
+        }
+
+        let to_player =
+            (player_pose.translation.xy() - turret_pose.translation.xy()).to_precision();
+        let dist_to_player = to_player.mag();
+        if dist_to_player > TURRET_RANGE {
+            continue;
+        }
+        let dir_to_player = to_player / dist_to_player;
+
+        // only shoot with line of sight, same as bats spotting the player
+        let Some(hit) = physics_query::raycast(
+            &game.physics,
+            sf::Ray {
+                start: turret_pose.translation.xy().to_precision(),
+                dir: sf::math::UnitDVec2::new_unchecked(dir_to_player),
+                length: dist_to_player,
+            },
+            physics_query::LINE_OF_SIGHT,
+        ) else {
+            continue;
+        };
+        let sees_player = game
+            .physics
+            .entity_set
+            .get_collider(hit.collider)
+            .is_some_and(|coll| coll.layer == physics_layers::PLAYER);
+        if !sees_player {
+            continue;
+        }
+
+        turret.cooldown = TURRET_FIRE_INTERVAL;
+        shots.push((turret_pose.translation.xy(), dir_to_player));
+    }
+
+    for (pos, dir) in shots {
+        spawn_projectile(game, pos, dir, assets.enemy_projectile_mesh);
+    }
+}
+
+pub fn spawn_projectile(game: &mut sf::Game, pos: sf::Vec2, dir: sf::DVec2, mesh: sf::MeshId) {
+    let pose = sf::PoseBuilder::new().with_position(pos).build();
+    let body = sf::Body::new_particle(0.1).ignore_gravity();
+    let body = game.physics.entity_set.insert_body(body);
+    if let Some(body) = game.physics.entity_set.get_body_mut(body) {
+        body.velocity.linear = dir * PROJECTILE_SPEED;
+    }
+    let coll = sf::Collider::new_circle(PROJECTILE_RADIUS)
+        .with_layer(physics_layers::ENEMY_BULLET)
+        .sensor();
+    let coll = game.physics.entity_set.attach_collider(body, coll);
+
+    let projectile = EnemyProjectile {
+        frames_left: PROJECTILE_LIFETIME,
+    };
+    game.world.spawn((projectile, pose, body, coll, mesh));
+}
+
+/// Remove projectiles fired by any enemy that hit something solid
+/// or have been around for too long.
+/// Hitting the player is handled on the player's side.
+pub fn tick_projectiles(game: &mut sf::Game) {
+    let mut despawn_queue: Vec<sf::hecs::Entity> = Vec::new();
+    for (ent, (projectile, coll_key)) in game
+        .world
+        .query_mut::<(&mut EnemyProjectile, &sf::ColliderKey)>()
+    {
+        projectile.frames_left = projectile.frames_left.saturating_sub(1);
+        let mut hit_solid = false;
+        for cont in game.physics.contacts_for_collider(*coll_key) {
+            if game
+                .physics
+                .entity_set
+                .get_collider(cont.colliders[1])
+                .is_some_and(|coll| coll.layer == physics_layers::SOLID)
+            {
+                hit_solid = true;
+            }
+        }
+        if hit_solid || projectile.frames_left == 0 {
+            despawn_queue.push(ent);
+        }
+    }
+
+    for ent in despawn_queue {
+        game.world.despawn(ent).ok();
+    }
+}
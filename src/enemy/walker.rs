@@ -0,0 +1,121 @@
+use rand::Rng;
+use starframe::{self as sf, math::ConvertPrecision};
+
+use super::{Enemy, EnemyType, Health, RollerReaction};
+use crate::{
+    physics_layers,
+    physics_query::{self, LayerMask},
+    player::PlayerState,
+    Assets,
+};
+
+pub const WALKER_WIDTH: f64 = 0.8;
+pub const WALKER_HEIGHT: f64 = 0.6;
+const WALKER_SPEED: f64 = 1.5;
+const WALKER_HP: f32 = 1.;
+/// How far past its own edges the walker looks for walls and the ground.
+const LOOK_DISTANCE: f64 = 0.1;
+/// What walkers can stand on. Clouds aren't included since walkers fall through them,
+/// so they count as the ground ending.
+const GROUND: LayerMask = LayerMask::only(&[physics_layers::SOLID]);
+/// What walkers turn around at, including other enemies so they don't push against each other.
+const WALLS: LayerMask = LayerMask::only(&[physics_layers::SOLID, physics_layers::ENEMY]);
+
+pub static WALKER: EnemyType = EnemyType {
+    symbol: Some('g'),
+    spawn,
+    tick,
+    on_hit: super::damage_on_hit,
+    roller_reaction: RollerReaction::Shred { score: 50 },
+};
+
+/// Walks back and forth on solid ground,
+/// turning around at walls and before walking off a ledge.
+#[derive(Clone, Copy, Debug)]
+pub struct Walker {
+    /// -1 for left, 1 for right
+    pub dir: f64,
+}
+
+fn spawn(game: &mut sf::Game, assets: &Assets, pos: sf::Vec2) -> sf::hecs::Entity {
+    let pose = sf::PoseBuilder::new().with_position(pos).build();
+    let body = sf::Body::new_particle(1.);
+    let body = game.physics.entity_set.insert_body(body);
+    let coll = assets.walker_collider.with_layer(physics_layers::ENEMY);
+    let coll = game.physics.entity_set.attach_collider(body, coll);
+    let mesh = assets.walker_mesh;
+
+    let dir = if rand::thread_rng().gen_bool(0.5) {
+        -1.
+    } else {
+        1.
+    };
+
+    game.world.spawn((
+        Enemy { ty: &WALKER },
+        Walker { dir },
+        Health::new(WALKER_HP, mesh, assets.walker_flash_mesh),
+        pose,
+        body,
+        coll,
+        mesh,
+    ))
+}
+
+fn tick(game: &mut sf::Game, _assets: &Assets, _player: &PlayerState) {
+    for (_, (walker, health, pose, body_key, coll_key)) in game.world.query_mut::<(
+        &mut Walker,
+        &Health,
+        &sf::Pose,
+        &sf::BodyKey,
+        &sf::ColliderKey,
+    )>() {
+        // let knockback play out
+        if health.is_stunned() {
+            continue;
+        }
+        let pos: sf::DVec2 = pose.translation.xy().to_precision();
+        let half_width = WALKER_WIDTH / 2.;
+        let half_height = WALKER_HEIGHT / 2.;
+
+        let down = sf::math::UnitDVec2::new_unchecked(sf::DVec2::new(0., -1.));
+        let ground_below = |start: sf::DVec2| {
+            physics_query::raycast(
+                &game.physics,
+                sf::Ray {
+                    start,
+                    dir: down,
+                    length: half_height + LOOK_DISTANCE,
+                },
+                GROUND,
+            )
+            .is_some()
+        };
+
+        // don't turn around in the air, just fall
+        if ground_below(pos) {
+            // the ray starts inside the walker itself, skip that
+            let hit_wall = physics_query::raycast_all(
+                &game.physics,
+                sf::Ray {
+                    start: pos,
+                    dir: sf::math::UnitDVec2::new_unchecked(sf::DVec2::new(walker.dir, 0.)),
+                    length: half_width + LOOK_DISTANCE,
+                },
+                WALLS,
+            )
+            .iter()
+            .any(|hit| hit.collider != *coll_key);
+            let ground_ahead =
+                ground_below(pos + sf::DVec2::new(walker.dir * (half_width + LOOK_DISTANCE), 0.));
+            if hit_wall || !ground_ahead {
+                walker.dir = -walker.dir;
+            }
+        }
+
+        let Some(body) = game.physics.entity_set.get_body_mut(*body_key) else {
+            continue;
+        };
+        body.velocity.linear.x = walker.dir * WALKER_SPEED;
+    }
+}
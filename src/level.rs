@@ -2,6 +2,8 @@ use itertools::Itertools;
 use rand::{seq::SliceRandom, Rng};
 use starframe as sf;
 
+pub mod director;
+pub mod oneway;
 pub mod tile;
 use tile::Tile;
 
@@ -13,9 +15,23 @@ pub const CHUNK_HEIGHT: i32 = 8;
 pub const LEVEL_HEIGHT: i32 = 30;
 /// Height seen on camera at any given time
 pub const VIEW_HEIGHT: f32 = 14.;
+/// Position far outside the level where entities that are reused
+/// instead of despawned (bullets, pips etc.) are kept while not in use.
+pub const PARKING_POS: [f32; 2] = [-1000., -1000.];
+
+/// Move an entity out of sight to `PARKING_POS`.
+pub fn park(pose: &mut sf::Pose) {
+    pose.translation.x = PARKING_POS[0];
+    pose.translation.y = PARKING_POS[1];
+}
 
 pub struct LevelGenerator {
     patterns: Vec<String>,
+    /// leave enemy spawning to a `SpawnDirector` at runtime
+    /// instead of picking enemies up front
+    pub use_director: bool,
+    // possible enemy spawn points of each chunk, kept for the director
+    spawn_points: Vec<Vec<(Tile, i32, i32)>>,
 }
 
 impl LevelGenerator {
@@ -36,7 +52,17 @@ impl LevelGenerator {
             })
             .collect();
 
-        Self { patterns }
+        Self {
+            patterns,
+            use_director: false,
+            spawn_points: Vec::new(),
+        }
+    }
+
+    /// Take the enemy spawn points gathered during the last `generate`, indexed by chunk.
+    /// Empty unless `use_director` is set.
+    pub fn take_spawn_points(&mut self) -> Vec<Vec<(Tile, i32, i32)>> {
+        std::mem::take(&mut self.spawn_points)
     }
 
     pub fn generate(&mut self, game: &mut sf::Game, assets: &super::Assets) {
@@ -93,15 +119,23 @@ impl LevelGenerator {
             }
         }
 
-        // spawn a "you win" text at the top of the level
-        // (no time to make an actual win animation)
-        let top_y = (LEVEL_HEIGHT as f32 + 0.5) * CHUNK_HEIGHT as f32;
-        let pose = sf::PoseBuilder::new()
-            .with_position([halfway_width, top_y])
-            .with_depth(-10.)
-            .build();
-        game.world.spawn((pose, assets.you_win_mesh));
+        // boss arena in the chunk above the generated level,
+        // with ledges on the sides and in the middle to fight from
+        let arena_bottom = LEVEL_HEIGHT * CHUNK_HEIGHT;
+        for x in 0..3 {
+            Tile::GroundUnbreakable.spawn(game, assets, (x, arena_bottom + 1));
+            Tile::GroundUnbreakable.spawn(game, assets, (TILEMAP_WIDTH - 1 - x, arena_bottom + 1));
+        }
+        for x in 8..12 {
+            Tile::GroundUnbreakable.spawn(game, assets, (x, arena_bottom + 3));
+        }
+        let boss_pos = sf::Vec2::new(
+            halfway_width,
+            arena_bottom as f32 + crate::enemy::boss::HOVER_HEIGHT as f32,
+        );
+        (crate::enemy::boss::BOSS.spawn)(game, assets, boss_pos);
 
+        let top_y = (LEVEL_HEIGHT as f32 + 0.5) * CHUNK_HEIGHT as f32;
         // also a little Barbuta-based easter egg in case somebody bounces on enemies really high
         let pose = sf::PoseBuilder::new()
             .with_position([halfway_width, top_y + 2. * CHUNK_HEIGHT as f32])
@@ -112,6 +146,7 @@ impl LevelGenerator {
 
     fn gen_tiles(&mut self, game: &mut sf::Game, assets: &super::Assets) {
         let mut rng = rand::thread_rng();
+        self.spawn_points.clear();
         for chunk_idx in 0..LEVEL_HEIGHT {
             // for enemies, patterns only mark possible spawn locations
             // and we pick a random number of them to actually spawn
@@ -143,6 +178,11 @@ impl LevelGenerator {
                 }
             }
 
+            if self.use_director {
+                self.spawn_points.push(possible_enemy_spawns);
+                continue;
+            }
+
             possible_enemy_spawns.shuffle(&mut rng);
             let enemies_in_chunk = if chunk_idx == 0 {
                 0
@@ -0,0 +1,111 @@
+use std::collections::VecDeque;
+
+use rand::seq::SliceRandom;
+use starframe as sf;
+
+use super::{tile::Tile, CHUNK_HEIGHT, LEVEL_HEIGHT, VIEW_HEIGHT};
+use crate::{
+    player::{PlayerEvent, PlayerState},
+    Assets,
+};
+
+/// Distance above the top of the screen at which a chunk counts as coming into view.
+/// Spawning a bit early hides enemies popping into existence.
+const SPAWN_MARGIN: f32 = 2.;
+/// Most enemies spawned in a single chunk.
+const MAX_ENEMIES_PER_CHUNK: f32 = 4.;
+/// Enemies per chunk at the bottom of the level with average play.
+const BASE_INTENSITY: f32 = 1.;
+/// Extra enemies per chunk at the top of the level.
+const HEIGHT_INTENSITY: f32 = 1.5;
+/// Extra enemies per chunk for each tile per second the player climbs.
+const SPEED_INTENSITY: f32 = 0.5;
+/// Fewer enemies per chunk for each recent hit the player has taken.
+const DAMAGE_INTENSITY: f32 = 0.75;
+/// Seconds over which the climbing speed is measured.
+const SPEED_WINDOW: f32 = 5.;
+/// How much of the recent damage is forgotten each second.
+const DAMAGE_DECAY_PER_SEC: f32 = 0.1;
+
+/// Decides which enemy spawn points to activate as chunks come into view,
+/// spawning more enemies for players who are doing well
+/// and easing off for players who are getting hit a lot.
+pub struct SpawnDirector {
+    // possible spawn points of each chunk, emptied when the chunk is spawned
+    spawn_points: Vec<Vec<(Tile, i32, i32)>>,
+    next_chunk: usize,
+    // player heights over the last `SPEED_WINDOW` seconds
+    heights: VecDeque<f32>,
+    // climbing speed over the measurement window in tiles per second
+    climb_speed: f32,
+    // number of recent hits taken, decays over time
+    recent_damage: f32,
+}
+
+impl SpawnDirector {
+    pub fn new(spawn_points: Vec<Vec<(Tile, i32, i32)>>) -> Self {
+        Self {
+            spawn_points,
+            // the first chunk is the starting area, never spawn anything there
+            next_chunk: 1,
+            heights: VecDeque::new(),
+            climb_speed: 0.,
+            recent_damage: 0.,
+        }
+    }
+
+    /// Track how the player is doing and spawn enemies in chunks that are coming into view.
+    /// Call once per update after moving the camera.
+    pub fn tick(
+        &mut self,
+        game: &mut sf::Game,
+        assets: &Assets,
+        player: &PlayerState,
+        camera_height: f32,
+    ) {
+        let dt = game.dt_fixed as f32;
+
+        self.recent_damage *= 1. - DAMAGE_DECAY_PER_SEC * dt;
+        if player.events().contains(&PlayerEvent::Damaged) {
+            self.recent_damage += 1.;
+        }
+
+        if let Ok(pose) = game.world.get::<&sf::Pose>(player.entity) {
+            self.heights.push_back(pose.translation.y);
+        }
+        let window_len = (SPEED_WINDOW / dt).ceil() as usize;
+        while self.heights.len() > window_len {
+            self.heights.pop_front();
+        }
+        if let (Some(first), Some(last)) = (self.heights.front(), self.heights.back()) {
+            let window_secs = (self.heights.len() - 1) as f32 * dt;
+            if window_secs > 0. {
+                self.climb_speed = ((last - first) / window_secs).max(0.);
+            }
+        }
+
+        let view_top = camera_height + VIEW_HEIGHT / 2. + SPAWN_MARGIN;
+        while self.next_chunk < self.spawn_points.len() {
+            let chunk_bottom = (self.next_chunk as i32 * CHUNK_HEIGHT) as f32;
+            if chunk_bottom > view_top {
+                break;
+            }
+            self.spawn_chunk(game, assets, self.next_chunk);
+            self.next_chunk += 1;
+        }
+    }
+
+    fn spawn_chunk(&mut self, game: &mut sf::Game, assets: &Assets, chunk_idx: usize) {
+        let height_t = chunk_idx as f32 / LEVEL_HEIGHT as f32;
+        let intensity =
+            BASE_INTENSITY + HEIGHT_INTENSITY * height_t + SPEED_INTENSITY * self.climb_speed
+                - DAMAGE_INTENSITY * self.recent_damage;
+        let enemy_count = intensity.clamp(0., MAX_ENEMIES_PER_CHUNK).round() as usize;
+
+        let mut points = std::mem::take(&mut self.spawn_points[chunk_idx]);
+        points.shuffle(&mut rand::thread_rng());
+        for (tile, x, y) in points.into_iter().take(enemy_count) {
+            tile.spawn(game, assets, (x, y));
+        }
+    }
+}
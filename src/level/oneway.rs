@@ -0,0 +1,61 @@
+use starframe as sf;
+
+use crate::{physics_layers, player::PlayerState};
+
+/// Leeway for considering the player to be above a platform.
+/// The player sinks into the platform slightly while standing on it,
+/// so this needs to be a little above zero.
+const ABOVE_TOLERANCE: f32 = 0.1;
+
+/// Component for platforms that the player can jump through from below
+/// but stand on from above.
+///
+/// sf note: this is implemented by swapping the collider
+/// between a layer that collides with the player and one that doesn't.
+/// it would be nice to have colliders that only resolve collisions in one direction
+/// in the engine instead
+#[derive(Clone, Copy, Debug, Default)]
+pub struct OneWayPlatform {
+    /// set when the player drops down through the platform,
+    /// keeping it passable until the player is fully below it
+    pub dropped_through: bool,
+}
+
+/// Make one-way platforms solid for the player only when the player is above them.
+/// Call once per update after moving the player and before the physics step.
+pub fn update_platforms(game: &mut sf::Game, player: &PlayerState) {
+    let Ok((player_pose,)) = game.world.query_one_mut::<(&sf::Pose,)>(player.entity) else {
+        return;
+    };
+    let half_height = crate::player::COLLIDER_HEIGHT as f32 / 2.;
+    let player_bottom = player_pose.translation.y - half_height;
+    let player_top = player_pose.translation.y + half_height;
+
+    for (_, (platform, pose, coll_key)) in
+        game.world
+            .query_mut::<(&mut OneWayPlatform, &sf::Pose, &sf::ColliderKey)>()
+    {
+        // platforms are always one tile in size
+        let platform_top = pose.translation.y + 0.5;
+        let platform_bottom = pose.translation.y - 0.5;
+
+        if platform.dropped_through
+            && (player_top < platform_bottom || player_bottom > platform_top + 1.)
+        {
+            // cleared the platform by falling through it,
+            // or jumped back up far enough that it should catch us again
+            platform.dropped_through = false;
+        }
+
+        let is_solid = !platform.dropped_through && player_bottom >= platform_top - ABOVE_TOLERANCE;
+        let layer = if is_solid {
+            physics_layers::ONEWAY_ACTIVE
+        } else {
+            physics_layers::ONEWAY_INACTIVE
+        };
+
+        if let Some(coll) = game.physics.entity_set.get_collider_mut(*coll_key) {
+            coll.layer = layer;
+        }
+    }
+}
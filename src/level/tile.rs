@@ -3,7 +3,8 @@ use core::f32;
 use rand::Rng;
 use starframe as sf;
 
-use crate::{enemy::Enemy, Assets};
+use super::oneway::OneWayPlatform;
+use crate::{enemy::EnemyType, weapon::WeaponUpgrade, Assets};
 
 #[derive(Clone, Copy, Debug)]
 pub enum Tile {
@@ -13,7 +14,8 @@ pub enum Tile {
     GroundStrong,
     // unbreakable ground only at the starting platform
     GroundUnbreakable,
-    Enemy(Enemy),
+    Enemy(&'static EnemyType),
+    Pickup(WeaponUpgrade),
 }
 
 /// State that tracks when a block needs to break
@@ -26,6 +28,12 @@ pub struct BreakableTile {
 
 impl Tile {
     pub fn pick(c: char) -> Self {
+        // enemies follow a different spawning logic,
+        // so they're always picked here and handled elsewhere
+        if let Some(enemy) = EnemyType::from_symbol(c) {
+            return Self::Enemy(enemy);
+        }
+
         let mut rng = rand::thread_rng();
         // some tiles only spawn occasionally,
         // represented by the probability given here
@@ -36,10 +44,8 @@ impl Tile {
             'w' => (Self::GroundWeak, 0.5),
             'C' => (Self::Cloud, 1.),
             'c' => (Self::Cloud, 0.5),
-            // enemies follow a different spawning logic,
-            // just give them a probability 1 here
-            // and handle that elsewhere
-            'b' => (Self::Enemy(Enemy::bat()), 1.),
+            // pickups are rare, so they're always a random chance
+            'u' => (Self::Pickup(WeaponUpgrade::random()), 0.2),
             _ => (Self::Empty, 1.),
         };
 
@@ -56,7 +62,7 @@ impl Tile {
 
     pub fn time_to_break(&self) -> Option<f32> {
         match self {
-            Self::GroundUnbreakable | Self::Empty | Self::Enemy(_) => None,
+            Self::GroundUnbreakable | Self::Empty | Self::Enemy(_) | Self::Pickup(_) => None,
             Self::GroundStrong => Some(2.),
             Self::GroundWeak => Some(0.75),
             Self::Cloud => Some(0.3),
@@ -78,7 +84,11 @@ impl Tile {
 
         // let enemies handle their own spawning logic
         if let Self::Enemy(enemy) = self {
-            enemy.spawn(game, assets, ent_pos);
+            (enemy.spawn)(game, assets, ent_pos);
+            return;
+        }
+        if let Self::Pickup(upgrade) = self {
+            upgrade.spawn_pickup(game, assets, ent_pos);
             return;
         }
 
@@ -92,10 +102,15 @@ impl Tile {
             Self::GroundUnbreakable | Self::GroundStrong => assets.block_stone_mesh,
             Self::GroundWeak => assets.block_wood_mesh,
             Self::Cloud => assets.cloud_mesh,
-            Self::Empty | Self::Enemy(_) => unreachable!(),
+            Self::Empty | Self::Enemy(_) | Self::Pickup(_) => unreachable!(),
         };
 
         let ent = game.world.spawn((pose, coll_key, mesh_id));
+        if self.can_jump_through() {
+            game.world
+                .insert_one(ent, OneWayPlatform::default())
+                .unwrap();
+        }
         if let Some(time_to_break) = self.time_to_break() {
             let breakable = BreakableTile {
                 time_to_break,
@@ -2,14 +2,25 @@ use starframe as sf;
 
 pub mod assets;
 pub use assets::Assets;
+pub mod camera;
+use camera::{CameraController, CameraMode};
 pub mod enemy;
 use enemy::Enemy;
 pub mod level;
+pub mod movement_config;
+use movement_config::MovementConfig;
 pub mod physics_layers;
+pub mod physics_query;
 pub mod player;
 use player::PlayerState;
+pub mod score;
+use score::Score;
 pub mod spike_roller;
 use spike_roller::SpikeRoller;
+pub mod viewport;
+use viewport::{ScaleMode, Viewport};
+pub mod weapon;
+use weapon::BulletPool;
 
 fn main() -> Result<(), Box<dyn std::error::Error>> {
     let window = sf::winit::window::WindowBuilder::new()
@@ -35,32 +46,136 @@ fn main() -> Result<(), Box<dyn std::error::Error>> {
     Ok(())
 }
 
+/// Length of the sequence between the player dying and the game over screen.
+const DEATH_SEQUENCE_FRAMES: u32 = 120;
+/// Frame of the death sequence where the screen starts fading out.
+const DEATH_FADE_START_FRAME: u32 = 45;
+const DEATH_SHAKE_FRAMES: u32 = 30;
+const DEATH_SHAKE_AMPLITUDE: f32 = 0.3;
+const PAUSE_KEY: sf::Key = sf::Key::Escape;
+/// Advances the game by one tick while paused. Only available in debug builds.
+const STEP_KEY: sf::Key = sf::Key::Period;
+
 pub struct State {
     assets: Assets,
     level_gen: level::LevelGenerator,
+    movement: MovementConfig,
     camera: sf::Camera,
+    camera_ctl: CameraController,
+    viewport: Viewport,
     env_map: sf::EnvironmentMap,
+    // brightness of the scene, lowered to fade out on death
+    brightness: f32,
     state: GameplayState,
     player: PlayerState,
+    bullets: BulletPool,
     spike_roller: SpikeRoller,
+    score: Score,
+    director: Option<level::director::SpawnDirector>,
 }
 
 #[derive(Clone, Copy, Debug)]
 pub enum GameplayState {
     Playing,
+    /// nothing moves until unpaused
+    Paused,
+    /// the player has been hit and is flying off the screen,
+    /// the world keeps running until the fade to game over
+    Dying {
+        frames: u32,
+    },
     GameOver,
+    /// the boss has been defeated
+    Won,
+}
+
+fn create_env_map() -> sf::EnvironmentMap {
+    let mut env_map = sf::EnvironmentMap::preset_night();
+    env_map.lights.clear();
+    env_map.ambient.iter_mut().for_each(|c| *c *= 3.);
+    env_map
 }
 
 impl State {
+    /// Scale the ambient light and background color, used to fade the scene out.
+    fn set_brightness(&mut self, brightness: f32) {
+        let brightness = brightness.clamp(0., 1.);
+        self.brightness = brightness;
+        self.env_map = create_env_map();
+        self.env_map
+            .ambient
+            .iter_mut()
+            .for_each(|c| *c *= brightness);
+    }
+
     fn reset(&mut self, game: &mut sf::Game) {
         // sf note: game.clear_state probably shouldn't automatically clear graphics too
         game.world.clear();
         game.physics.clear();
         game.hecs_sync.clear();
         self.level_gen.generate(game, &self.assets);
-        self.camera.pose.translation.y = 3.;
+        self.director = self.create_director();
+        self.camera_ctl.reset(&mut self.camera);
         self.player = PlayerState::spawn(game, &self.assets);
+        self.bullets = BulletPool::spawn(game, &self.assets);
         self.spike_roller = SpikeRoller::spawn(game, &self.assets);
+        self.score = Score::spawn(game, &self.assets);
+        self.viewport = Viewport::spawn(game, &self.assets, self.viewport.scale_mode);
+        self.set_brightness(1.);
+    }
+
+    fn create_director(&mut self) -> Option<level::director::SpawnDirector> {
+        self.level_gen
+            .use_director
+            .then(|| level::director::SpawnDirector::new(self.level_gen.take_spawn_points()))
+    }
+
+    /// Run one tick of normal gameplay.
+    fn tick_playing(&mut self, game: &mut sf::Game) {
+        self.player
+            .tick(game, &self.assets, &self.movement, &mut self.bullets);
+        self.score.tick(game, &self.player);
+        Enemy::tick(game, &self.assets, &self.player, &self.spike_roller);
+        level::oneway::update_platforms(game, &self.player);
+        game.physics_tick(
+            &sf::forcefield::Gravity(sf::DVec2::new(0., -self.movement.gravity)),
+            None,
+        );
+
+        self.camera_ctl.tick(game, &self.player, &mut self.camera);
+        if let Some(director) = &mut self.director {
+            director.tick(game, &self.assets, &self.player, self.camera_ctl.height());
+        }
+        let roller_result = self.spike_roller.tick(
+            game,
+            &self.assets,
+            &self.camera,
+            &self.player,
+            &mut self.score,
+        );
+
+        weapon::handle_bullets(game, &self.assets, &self.camera);
+        level::tile::break_tiles(game);
+
+        enemy::boss::update_health_bar(game, &self.camera);
+        self.score.update_display(game, &self.camera);
+        if enemy::boss::is_defeated(game) {
+            self.state = GameplayState::Won;
+            // same deal as the game over message
+            let pose = sf::PoseBuilder::new()
+                .with_position(self.camera.pose.translation.xy())
+                .with_depth(-10.)
+                .build();
+            game.world.spawn((pose, self.assets.you_win_mesh));
+            return;
+        }
+
+        if roller_result.player_hit {
+            self.player.kill(game);
+            self.camera_ctl
+                .shake(DEATH_SHAKE_AMPLITUDE, DEATH_SHAKE_FRAMES);
+            self.state = GameplayState::Dying { frames: 0 };
+        }
     }
 }
 
@@ -69,33 +184,42 @@ impl sf::GameState for State {
         physics_layers::setup(&mut game.physics);
 
         let assets = Assets::load(game);
+        let movement = MovementConfig::load().unwrap_or_else(|err| {
+            eprintln!("failed to load movement config, using defaults: {err}");
+            MovementConfig::default()
+        });
         let mut level_gen = level::LevelGenerator::new(include_str!("level/patterns.txt"));
         level_gen.generate(game, &assets);
 
         let mut camera = sf::Camera::new();
+        let mut camera_ctl = CameraController::new(CameraMode::Follow);
         camera.pose.translation.x = level::LEVEL_WIDTH / 2.;
-        camera.pose.translation.y = 3.;
-        // always scale the view to the same height
-        // (this can lose sight of the level edges if the window is too narrow.
-        // sf note: add a way to enforce 16:9 aspect ratio)
-        camera.view_width = 1.;
-        camera.view_height = level::VIEW_HEIGHT;
+        camera_ctl.reset(&mut camera);
+        // view size is set by the viewport every frame to keep the aspect ratio fixed
 
-        let mut env_map = sf::EnvironmentMap::preset_night();
-        env_map.lights.clear();
-        env_map.ambient.iter_mut().for_each(|c| *c *= 3.);
+        let env_map = create_env_map();
 
         let player = PlayerState::spawn(game, &assets);
+        let bullets = BulletPool::spawn(game, &assets);
         let spike_roller = SpikeRoller::spawn(game, &assets);
+        let score = Score::spawn(game, &assets);
+        let viewport = Viewport::spawn(game, &assets, ScaleMode::Fit);
 
         Self {
             assets,
             level_gen,
+            movement,
             camera,
+            camera_ctl,
+            viewport,
             state: GameplayState::Playing,
             env_map,
+            brightness: 1.,
             player,
+            bullets,
             spike_roller,
+            score,
+            director: None,
         }
     }
 
@@ -117,20 +241,68 @@ impl sf::GameState for State {
             game.renderer
                 .set_lighting_quality(sf::LightingQualityConfig::HIGH);
         }
+        // auto-scrolling camera for challenge runs
+        if game.input.button(sf::Key::F2.into()) {
+            self.camera_ctl.toggle_auto_scroll();
+        }
+        // pixel-perfect scaling at the cost of bigger black bars
+        if game.input.button(sf::Key::F3.into()) {
+            self.viewport.toggle_scale_mode();
+        }
+        // adaptive enemy spawning, takes effect on the next run
+        if game.input.button(sf::Key::F4.into()) {
+            self.level_gen.use_director = !self.level_gen.use_director;
+        }
+        // reload movement parameters so they can be tuned without restarting
+        if game.input.button(sf::Key::F5.into()) {
+            match MovementConfig::load() {
+                Ok(conf) => self.movement = conf,
+                Err(err) => eprintln!("failed to reload movement config: {err}"),
+            }
+        }
 
         match self.state {
             GameplayState::Playing => {
-                self.player.tick(game, &self.assets);
-                Enemy::tick(game, &self.player);
-                game.physics_tick(&sf::forcefield::Gravity(sf::DVec2::new(0., -15.)), None);
-
-                self.player.move_camera(game, &mut self.camera);
-                let roller_result = self.spike_roller.tick(game, &self.camera, &self.player);
-
-                player::handle_bullets(game, &self.camera);
+                if game.input.button(PAUSE_KEY.into()) {
+                    self.state = GameplayState::Paused;
+                } else {
+                    self.tick_playing(game);
+                }
+            }
+            GameplayState::Paused => {
+                if game.input.button(PAUSE_KEY.into()) {
+                    self.state = GameplayState::Playing;
+                } else if cfg!(debug_assertions) && game.input.button(STEP_KEY.into()) {
+                    // advance exactly one tick for debugging.
+                    // the player may die during it, in which case we stay in the death sequence
+                    self.tick_playing(game);
+                    if matches!(self.state, GameplayState::Playing) {
+                        self.state = GameplayState::Paused;
+                    }
+                }
+            }
+            GameplayState::Dying { frames } => {
+                // keep the world running while the player flies off
+                Enemy::tick(game, &self.assets, &self.player, &self.spike_roller);
+                game.physics_tick(
+                    &sf::forcefield::Gravity(sf::DVec2::new(0., -self.movement.gravity)),
+                    None,
+                );
+                // the player doesn't tick while dead but still animates
+                self.player.animate(game, &self.assets);
+                weapon::handle_bullets(game, &self.assets, &self.camera);
                 level::tile::break_tiles(game);
 
-                if roller_result.player_hit {
+                self.camera_ctl.tick(game, &self.player, &mut self.camera);
+                self.score.update_display(game, &self.camera);
+                if frames >= DEATH_FADE_START_FRAME {
+                    let fade_len = (DEATH_SEQUENCE_FRAMES - DEATH_FADE_START_FRAME) as f32;
+                    let fade_t = (frames - DEATH_FADE_START_FRAME) as f32 / fade_len;
+                    self.set_brightness(1. - fade_t);
+                }
+
+                if frames >= DEATH_SEQUENCE_FRAMES {
+                    self.set_brightness(1.);
                     self.state = GameplayState::GameOver;
                     // spawn a "game over" message in the world
                     // (we don't have text/menu type stuff in starframe yet)
@@ -139,9 +311,11 @@ impl sf::GameState for State {
                         .with_depth(-10.)
                         .build();
                     game.world.spawn((pose, self.assets.game_over_mesh));
+                } else {
+                    self.state = GameplayState::Dying { frames: frames + 1 };
                 }
             }
-            GameplayState::GameOver => {
+            GameplayState::GameOver | GameplayState::Won => {
                 if game.input.button(sf::ButtonQuery::kb(sf::Key::ShiftLeft)) {
                     self.reset(game);
                     self.state = GameplayState::Playing;
@@ -153,12 +327,27 @@ impl sf::GameState for State {
     }
 
     fn draw(&mut self, game: &mut sf::Game, dt: f32) {
+        // screen shake is applied only for drawing
+        // so that it doesn't affect anything that follows the camera
+        let cam_pos = self.camera.pose.translation;
+        // don't keep shaking while the game is paused
+        let shake = if matches!(self.state, GameplayState::Paused) {
+            sf::Vec2::zero()
+        } else {
+            self.camera_ctl.shake_offset()
+        };
+        self.camera.pose.translation.x += shake.x;
+        self.camera.pose.translation.y += shake.y;
+        self.viewport.update(game, &mut self.camera);
         self.camera.upload();
+        self.camera.pose.translation = cam_pos;
+
         game.graphics.update_animations(dt);
         game.renderer.set_environment_map(&self.env_map);
 
         let mut frame = game.renderer.begin_frame();
-        frame.set_clear_color([0.00802, 0.0137, 0.02732, 1.]);
+        let b = self.brightness;
+        frame.set_clear_color([0.00802 * b, 0.0137 * b, 0.02732 * b, 1.]);
         frame.draw_meshes(&mut game.graphics, &mut game.world, &self.camera);
     }
 }
@@ -0,0 +1,169 @@
+use std::str::FromStr;
+
+/// Location of the movement config file, relative to the working directory,
+/// or failing that, the crate root so that it's found when running from elsewhere.
+/// The file is optional, any values not given in it use the defaults below.
+pub const CONFIG_PATH: &str = "assets/movement.cfg";
+
+/// Tunable parameters for how the player moves.
+#[derive(Clone, Copy, Debug)]
+pub struct MovementConfig {
+    pub gravity: f64,
+    pub max_xspeed: f64,
+    pub jump_yspeed: f64,
+    pub enemy_bounce_yspeed_jump_held: f64,
+    pub enemy_bounce_yspeed_jump_released: f64,
+    pub coyote_time_frames: u32,
+    pub jump_buffer_frames: u32,
+    pub knockback_speed: f64,
+    pub knockback_frames: usize,
+    pub wall_jump_enabled: bool,
+    /// maximum falling speed when sliding down a wall
+    pub wall_slide_speed: f64,
+    pub wall_jump_xspeed: f64,
+    pub wall_jump_yspeed: f64,
+    /// frames after a wall jump where horizontal input is ignored
+    pub wall_jump_control_lock_frames: u32,
+    pub dash_speed: f64,
+    /// duration of a dash
+    pub dash_frames: usize,
+    /// frames after starting a dash before another one can be started
+    pub dash_cooldown_frames: u32,
+}
+
+impl Default for MovementConfig {
+    fn default() -> Self {
+        Self {
+            gravity: 15.,
+            max_xspeed: 7.,
+            jump_yspeed: 12.,
+            enemy_bounce_yspeed_jump_held: 10.,
+            enemy_bounce_yspeed_jump_released: 5.,
+            coyote_time_frames: 3,
+            jump_buffer_frames: 5,
+            knockback_speed: 15.,
+            knockback_frames: 60,
+            wall_jump_enabled: true,
+            wall_slide_speed: 2.,
+            wall_jump_xspeed: 8.,
+            wall_jump_yspeed: 11.,
+            wall_jump_control_lock_frames: 10,
+            dash_speed: 18.,
+            dash_frames: 10,
+            dash_cooldown_frames: 30,
+        }
+    }
+}
+
+#[derive(Clone, Debug)]
+pub struct ConfigError {
+    /// line where the error occurred, or 0 if the file couldn't be read at all
+    pub line: usize,
+    pub message: String,
+}
+
+impl std::fmt::Display for ConfigError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        if self.line == 0 {
+            write!(f, "{}: {}", CONFIG_PATH, self.message)
+        } else {
+            write!(f, "{} line {}: {}", CONFIG_PATH, self.line, self.message)
+        }
+    }
+}
+
+impl std::error::Error for ConfigError {}
+
+impl MovementConfig {
+    /// Load the config from `CONFIG_PATH`,
+    /// falling back to defaults if the file doesn't exist.
+    pub fn load() -> Result<Self, ConfigError> {
+        let path = Self::path();
+        match std::fs::read_to_string(&path) {
+            Ok(text) => Self::parse(&text),
+            Err(err) if err.kind() == std::io::ErrorKind::NotFound => {
+                // not an error since the file is optional,
+                // but say so in case someone is editing a file that isn't being read
+                eprintln!(
+                    "movement config not found at {}, using defaults",
+                    path.display()
+                );
+                Ok(Self::default())
+            }
+            Err(err) => Err(ConfigError {
+                line: 0,
+                message: err.to_string(),
+            }),
+        }
+    }
+
+    fn path() -> std::path::PathBuf {
+        let relative = std::path::Path::new(CONFIG_PATH);
+        if relative.exists() {
+            relative.to_path_buf()
+        } else {
+            std::path::Path::new(env!("CARGO_MANIFEST_DIR")).join(CONFIG_PATH)
+        }
+    }
+
+    /// Parse a config from lines of the form `key = value`.
+    /// Empty lines and lines starting with `#` are ignored.
+    pub fn parse(text: &str) -> Result<Self, ConfigError> {
+        let mut conf = Self::default();
+        for (line_idx, line) in text.lines().enumerate() {
+            let line_num = line_idx + 1;
+            let line = line.trim();
+            if line.is_empty() || line.starts_with('#') {
+                continue;
+            }
+            let Some((key, value)) = line.split_once('=') else {
+                return Err(ConfigError {
+                    line: line_num,
+                    message: "expected `key = value`".to_string(),
+                });
+            };
+            let (key, value) = (key.trim(), value.trim());
+
+            match key {
+                "gravity" => conf.gravity = parse_value(line_num, value)?,
+                "max_xspeed" => conf.max_xspeed = parse_value(line_num, value)?,
+                "jump_yspeed" => conf.jump_yspeed = parse_value(line_num, value)?,
+                "enemy_bounce_yspeed_jump_held" => {
+                    conf.enemy_bounce_yspeed_jump_held = parse_value(line_num, value)?
+                }
+                "enemy_bounce_yspeed_jump_released" => {
+                    conf.enemy_bounce_yspeed_jump_released = parse_value(line_num, value)?
+                }
+                "coyote_time_frames" => conf.coyote_time_frames = parse_value(line_num, value)?,
+                "jump_buffer_frames" => conf.jump_buffer_frames = parse_value(line_num, value)?,
+                "knockback_speed" => conf.knockback_speed = parse_value(line_num, value)?,
+                "knockback_frames" => conf.knockback_frames = parse_value(line_num, value)?,
+                "wall_jump_enabled" => conf.wall_jump_enabled = parse_value(line_num, value)?,
+                "wall_slide_speed" => conf.wall_slide_speed = parse_value(line_num, value)?,
+                "wall_jump_xspeed" => conf.wall_jump_xspeed = parse_value(line_num, value)?,
+                "wall_jump_yspeed" => conf.wall_jump_yspeed = parse_value(line_num, value)?,
+                "wall_jump_control_lock_frames" => {
+                    conf.wall_jump_control_lock_frames = parse_value(line_num, value)?
+                }
+                "dash_speed" => conf.dash_speed = parse_value(line_num, value)?,
+                "dash_frames" => conf.dash_frames = parse_value(line_num, value)?,
+                "dash_cooldown_frames" => conf.dash_cooldown_frames = parse_value(line_num, value)?,
+                _ => {
+                    return Err(ConfigError {
+                        line: line_num,
+                        message: format!("unknown key `{key}`"),
+                    })
+                }
+            }
+        }
+
+        Ok(conf)
+    }
+}
+
+fn parse_value<T: FromStr>(line: usize, value: &str) -> Result<T, ConfigError> {
+    value.parse().map_err(|_| ConfigError {
+        line,
+        message: format!("invalid value `{value}`"),
+    })
+}
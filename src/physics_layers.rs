@@ -1,18 +1,51 @@
 use starframe as sf;
 
+/// The default layer, used by solid tiles and walls.
+pub const SOLID: usize = 0;
 pub const PLAYER: usize = 1;
 pub const BULLET: usize = 2;
 // one-way platforms implemented by ignoring collisions with the player
-// until the player moves above them,
-// then swapping them to a layer that has collision with the player
+// while the player is below them,
+// and swapping them to a layer that has collision with the player
+// whenever the player is above (see `level::oneway`)
 pub const ONEWAY_INACTIVE: usize = 3;
 pub const ONEWAY_ACTIVE: usize = 4;
 pub const SPIKE_ROLLER: usize = 5;
 pub const ENEMY: usize = 6;
+pub const PICKUP: usize = 7;
+// the player after dying, falls through everything
+pub const PLAYER_DEAD: usize = 8;
+// projectiles fired by enemies, pass through everything but the player and solid tiles
+pub const ENEMY_BULLET: usize = 9;
 
 pub fn setup(physics: &mut sf::PhysicsWorld) {
     physics.mask_matrix.ignore(PLAYER, BULLET);
     physics.mask_matrix.ignore(PLAYER, ONEWAY_INACTIVE);
     physics.mask_matrix.ignore(ENEMY, ONEWAY_ACTIVE);
     physics.mask_matrix.ignore(ENEMY, ONEWAY_INACTIVE);
+    physics.mask_matrix.ignore(ENEMY, PICKUP);
+    for layer in [
+        BULLET,
+        ONEWAY_INACTIVE,
+        ONEWAY_ACTIVE,
+        SPIKE_ROLLER,
+        ENEMY,
+        PICKUP,
+        ENEMY_BULLET,
+    ] {
+        physics.mask_matrix.ignore(ENEMY_BULLET, layer);
+    }
+    for layer in [
+        SOLID,
+        PLAYER,
+        BULLET,
+        ONEWAY_INACTIVE,
+        ONEWAY_ACTIVE,
+        SPIKE_ROLLER,
+        ENEMY,
+        PICKUP,
+        ENEMY_BULLET,
+    ] {
+        physics.mask_matrix.ignore(PLAYER_DEAD, layer);
+    }
 }
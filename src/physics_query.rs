@@ -0,0 +1,128 @@
+use starframe as sf;
+
+use crate::physics_layers;
+
+/// Set of collision layers from `physics_layers` that a cast can hit.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct LayerMask(u32);
+
+impl LayerMask {
+    pub const fn all() -> Self {
+        Self(u32::MAX)
+    }
+
+    pub const fn only(layers: &[usize]) -> Self {
+        Self(0).with(layers)
+    }
+
+    pub const fn with(self, layers: &[usize]) -> Self {
+        let mut bits = self.0;
+        let mut i = 0;
+        while i < layers.len() {
+            bits |= 1 << layers[i];
+            i += 1;
+        }
+        Self(bits)
+    }
+
+    pub const fn without(self, layers: &[usize]) -> Self {
+        let mut bits = self.0;
+        let mut i = 0;
+        while i < layers.len() {
+            bits &= !(1 << layers[i]);
+            i += 1;
+        }
+        Self(bits)
+    }
+
+    pub const fn contains(self, layer: usize) -> bool {
+        self.0 & (1 << layer) != 0
+    }
+}
+
+/// Things that block line of sight: solid tiles, walls and the player themself.
+/// Clouds and other enemies can be seen through.
+pub const LINE_OF_SIGHT: LayerMask =
+    LayerMask::only(&[physics_layers::SOLID, physics_layers::PLAYER]);
+
+#[derive(Clone, Copy, Debug)]
+pub struct QueryHit {
+    pub collider: sf::ColliderKey,
+}
+
+/// Raycast that only hits colliders on the given layers,
+/// returning the nearest one.
+pub fn raycast(physics: &sf::PhysicsWorld, ray: sf::Ray, mask: LayerMask) -> Option<QueryHit> {
+    cast_filtered(physics, ray, mask, 1, |ray| {
+        physics.raycast(ray).map(|hit| (hit.collider, hit.t))
+    })
+    .pop()
+}
+
+/// Raycast that returns every collider on the given layers along the ray,
+/// ordered by distance.
+pub fn raycast_all(physics: &sf::PhysicsWorld, ray: sf::Ray, mask: LayerMask) -> Vec<QueryHit> {
+    cast_filtered(physics, ray, mask, usize::MAX, |ray| {
+        physics.raycast(ray).map(|hit| (hit.collider, hit.t))
+    })
+}
+
+/// Spherecast that returns every collider on the given layers along the ray,
+/// ordered by distance.
+pub fn spherecast_all(
+    physics: &sf::PhysicsWorld,
+    radius: f64,
+    ray: sf::Ray,
+    mask: LayerMask,
+) -> Vec<QueryHit> {
+    cast_filtered(physics, ray, mask, usize::MAX, |ray| {
+        physics
+            .spherecast(radius, ray)
+            .map(|hit| (hit.collider, hit.t))
+    })
+}
+
+// sf note: the engine only gives us the nearest hit of a cast,
+// so filtering and multiple hits are emulated here with repeated casts,
+// each starting a little past the previous hit.
+// it would be nicer and faster to do this in the engine
+fn cast_filtered(
+    physics: &sf::PhysicsWorld,
+    ray: sf::Ray,
+    mask: LayerMask,
+    max_hits: usize,
+    cast: impl Fn(sf::Ray) -> Option<(sf::ColliderKey, f64)>,
+) -> Vec<QueryHit> {
+    // distance to move forward after each hit.
+    // a cast starting inside something may hit that same thing again,
+    // in which case we just keep stepping forward until we're out of it
+    const STEP_PAST_HIT: f64 = 0.05;
+
+    // every cast moves us forward by at least `STEP_PAST_HIT`,
+    // so the number of casts is bounded by `ray.length / STEP_PAST_HIT`.
+    // there's no fixed cap on top of that because stopping early
+    // would report no hit (i.e. clear line of sight) when there might be one further along.
+    // rays in this game are at most about a screen long, which keeps this cheap enough
+    let mut hits: Vec<QueryHit> = Vec::new();
+    let mut traveled = 0.;
+    while traveled < ray.length && hits.len() < max_hits {
+        let Some((collider, t)) = cast(sf::Ray {
+            start: ray.start + traveled * *ray.dir,
+            dir: ray.dir,
+            length: ray.length - traveled,
+        }) else {
+            break;
+        };
+
+        let in_mask = physics
+            .entity_set
+            .get_collider(collider)
+            .is_some_and(|coll| mask.contains(coll.layer));
+        if in_mask && !hits.iter().any(|hit| hit.collider == collider) {
+            hits.push(QueryHit { collider });
+        }
+        traveled += t + STEP_PAST_HIT;
+    }
+
+    hits
+}
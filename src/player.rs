@@ -1,40 +1,83 @@
+use rand::Rng;
 use starframe as sf;
 
-use sf::math::ConvertPrecision;
+use crate::{
+    enemy::{turret::EnemyProjectile, Enemy, EnemyHit},
+    level::{oneway::OneWayPlatform, tile::BreakableTile},
+    movement_config::MovementConfig,
+    physics_layers,
+    weapon::{BulletPool, Weapon, WeaponPickup, WeaponUpgrade},
+};
 
-use crate::{enemy::Enemy, level::tile::BreakableTile};
+pub mod animation;
+pub use animation::{PlayerAnim, PlayerAnimator, PlayerTint};
+pub mod movement_state;
+pub use movement_state::{MovementState, PlayerEvent};
 
-const COLLIDER_WIDTH: f64 = 0.8;
+pub const COLLIDER_HEIGHT: f64 = 1.;
 
 const PLAYER_MASS: f64 = 1.;
-const MAX_XSPEED: f64 = 7.;
-const JUMP_YSPEED: f64 = 12.;
-const ENEMY_BOUNCE_YSPEED_JUMP_HELD: f64 = 10.;
-const ENEMY_BOUNCE_YSPEED_JUMP_RELEASED: f64 = 5.;
-const COYOTE_TIME_FRAMES: u32 = 3;
-const KNOCKBACK_SPEED: f64 = 15.;
-const KNOCKBACK_FRAMES: usize = 60;
-
-const BULLET_RADIUS: f64 = 0.35;
-const BULLET_SPEED: f64 = 25.;
-const BULLET_TILE_DAMAGE: f32 = 0.5;
+const DEATH_LAUNCH_XSPEED: f64 = 3.;
+const DEATH_LAUNCH_YSPEED: f64 = 14.;
+// visor is drawn on the front of the body, just in front of it in depth
+const VISOR_INSET: f64 = 0.15;
+const VISOR_HEIGHT: f64 = 0.2;
+const VISOR_DEPTH: f32 = -1.;
 
 pub struct PlayerState {
     pub entity: sf::hecs::Entity,
+    state: MovementState,
+    // everything that happened during the latest tick
+    events: Vec<PlayerEvent>,
     has_doublejump: bool,
-    frames_since_on_ground: u32,
+    has_dash: bool,
+    dash_cooldown_frames: u32,
+    // frames left for a jump press that couldn't be used yet
+    // to still trigger a jump when landing
+    jump_buffer_frames: u32,
     holding_jump: bool,
     // aim direction stored here
     // so that we can shoot in the previously pressed direction
     // if no direction is currently held
     aim_dir: sf::math::UnitDVec2,
-    knockback_frames: usize,
+    // horizontal direction last moved or aimed in, -1 or 1.
+    // used for dashing when aiming straight up or down,
+    // and shown by which side of the body the visor is on
+    facing: f64,
+    animator: PlayerAnimator,
+    // separate entity so that it can be moved to either side of the body
+    visor: sf::hecs::Entity,
 }
 
-pub struct Bullet {
-    // bullets store their movement direction and move manually
-    // in order to ensure they don't tunnel and only hit one thing at a time
-    dir: sf::math::UnitDVec2,
+/// Controls read at the start of a tick.
+struct PlayerInput {
+    lr: f64,
+    tb: f64,
+    jump_pressed: bool,
+    jump_held: bool,
+    jump_released: bool,
+    shoot: bool,
+    dash: bool,
+}
+
+/// Things learned from the player's contacts during a tick.
+#[derive(Default)]
+struct ContactInfo {
+    on_ground: bool,
+    // direction of a wall being touched, -1 for left and 1 for right
+    wall_dir: Option<f64>,
+    bounced_on_enemy: bool,
+    knockback_vel: Option<sf::DVec2>,
+    // enemies are hit after contacts are checked since their hooks need the whole game
+    enemies_hit: Vec<(sf::hecs::Entity, EnemyHit)>,
+    picked_upgrade: Option<WeaponUpgrade>,
+    // gather tiles to break into a buffer and apply at the end
+    // so that we don't need nested hecs queries
+    tiles_touched: Vec<sf::hecs::Entity>,
+    // one-way platforms stood on, and whether there's also something solid underneath
+    // (in which case we can't drop through)
+    platforms_below: Vec<sf::hecs::Entity>,
+    solid_below: bool,
 }
 
 impl PlayerState {
@@ -54,261 +97,524 @@ impl PlayerState {
         let coll = game.physics.entity_set.attach_collider(body, coll);
         let mesh = assets.player_mesh;
 
-        let entity = game.world.spawn((pose, coll, body, mesh));
+        let weapon = Weapon::default();
+
+        let entity = game.world.spawn((pose, coll, body, mesh, weapon));
+
+        let visor_pose = sf::PoseBuilder::new()
+            .with_position([0.5, 0.5])
+            .with_depth(VISOR_DEPTH)
+            .build();
+        let visor = game.world.spawn((visor_pose, assets.player_visor_mesh));
 
         Self {
             entity,
+            state: MovementState::Grounded,
+            events: Vec::new(),
             has_doublejump: true,
-            frames_since_on_ground: 0,
+            has_dash: true,
+            dash_cooldown_frames: 0,
+            jump_buffer_frames: 0,
             holding_jump: false,
             aim_dir: sf::math::UnitDVec2::unit_x(),
-            knockback_frames: 0,
+            facing: 1.,
+            animator: PlayerAnimator::default(),
+            visor,
         }
     }
 
-    pub fn tick(&mut self, game: &mut sf::Game, assets: &super::Assets) {
-        // gather tiles to break into a buffer and apply at the end
-        // so that we don't need nested hecs queries
-        let mut tiles_touched: Vec<sf::hecs::Entity> = Vec::new();
+    pub fn state(&self) -> MovementState {
+        self.state
+    }
 
-        // read controls
-        // TODO configurable keys, gamepad support
-        let lr_input = game.input.axis(sf::AxisQuery {
-            pos_btn: sf::Key::ArrowRight.into(),
-            neg_btn: sf::Key::ArrowLeft.into(),
-        });
-        let tb_input = game.input.axis(sf::AxisQuery {
-            pos_btn: sf::Key::ArrowUp.into(),
-            neg_btn: sf::Key::ArrowDown.into(),
-        });
-        let jump_btn = sf::ButtonQuery::kb(sf::Key::ShiftLeft);
-        let jump_pressed = game.input.button(jump_btn);
-        let jump_held = game.input.button(jump_btn.held());
-        let jump_released = game.input.button(jump_btn.released());
-        let shoot_input = game.input.button(sf::ButtonQuery::kb(sf::Key::KeyZ));
+    /// Everything that happened to the player during the latest tick.
+    pub fn events(&self) -> &[PlayerEvent] {
+        &self.events
+    }
 
-        let Ok((&pose, &coll_key)) = game
+    /// Put the player in the dead state where it no longer responds to input,
+    /// and launch it off the screen.
+    pub fn kill(&mut self, game: &mut sf::Game) {
+        self.transition(MovementState::Dead);
+
+        let Ok((body_key, coll_key)) = game
             .world
-            .query_one_mut::<(&sf::Pose, &sf::ColliderKey)>(self.entity)
+            .query_one_mut::<(&sf::BodyKey, &sf::ColliderKey)>(self.entity)
         else {
             return;
         };
+        if let Some(body) = game.physics.entity_set.get_body_mut(*body_key) {
+            let launch_x = rand::thread_rng().gen_range(-DEATH_LAUNCH_XSPEED..=DEATH_LAUNCH_XSPEED);
+            body.velocity.linear = sf::DVec2::new(launch_x, DEATH_LAUNCH_YSPEED);
+        }
+        // fall through everything on the way down
+        if let Some(coll) = game.physics.entity_set.get_collider_mut(*coll_key) {
+            coll.layer = physics_layers::PLAYER_DEAD;
+        }
+    }
 
-        // check for being on the ground and also begin destroy blocks touched
-        let mut is_on_ground = false;
-        // defer applying enemy bounce and knockback to when we have a reference to the player body
-        let mut bounced_on_enemy = false;
-        let mut knockback_vel: Option<sf::DVec2> = None;
-        for cont in game.physics.contacts_for_collider(coll_key) {
-            if let Some(ent) = game.hecs_sync.get_collider_entity(cont.colliders[1]) {
-                if let Ok((_, enemy_pose)) = game.world.query_one_mut::<(&Enemy, &sf::Pose)>(ent) {
-                    if cont.normal.y > -0.75 {
-                        // hit from the side, get knocked back
-                        knockback_vel = Some(if pose.translation.x < enemy_pose.translation.x {
-                            sf::DVec2::new(-KNOCKBACK_SPEED, 0.)
-                        } else {
-                            sf::DVec2::new(KNOCKBACK_SPEED, 0.)
-                        });
-                    } else {
-                        // on top of the enemy, bounce
-                        bounced_on_enemy = true;
-                    }
+    fn transition(&mut self, to: MovementState) {
+        if !self.state.same_kind(&to) {
+            self.events.push(PlayerEvent::StateChanged {
+                from: self.state,
+                to,
+            });
+        }
+        self.state = to;
+    }
 
-                    game.world.despawn(ent).ok();
-                }
+    pub fn tick(
+        &mut self,
+        game: &mut sf::Game,
+        assets: &super::Assets,
+        conf: &MovementConfig,
+        bullets: &mut BulletPool,
+    ) {
+        self.events.clear();
+        if self.state == MovementState::Dead {
+            return;
+        }
+
+        let input = PlayerInput::read(game);
+        let Some(contacts) = self.check_contacts(game, conf) else {
+            return;
+        };
+
+        let Ok((pose, body_key, weapon)) = game
+            .world
+            .query_one_mut::<(&sf::Pose, &sf::BodyKey, &mut Weapon)>(self.entity)
+        else {
+            return;
+        };
+
+        // copy the weapon out so that it can spawn bullets into the world,
+        // written back at the end
+        let mut weapon_state = *weapon;
+        weapon_state.tick();
+        if let Some(upgrade) = contacts.picked_upgrade {
+            weapon_state.apply_upgrade(upgrade);
+        }
+
+        let body = game
+            .physics
+            .entity_set
+            .get_body_mut(*body_key)
+            .expect("Player body disappeared unexpectedly");
+
+        self.update_movement(body, &input, &contacts, conf);
+
+        // shoot/aim
+
+        if input.lr != 0. || input.tb != 0. {
+            self.aim_dir = sf::math::UnitDVec2::new_normalize(sf::DVec2::new(input.lr, input.tb));
+        }
+        if input.shoot {
+            let pos = pose.translation.xy();
+            if weapon_state.fire(game, bullets, pos, self.aim_dir) {
+                self.events.push(PlayerEvent::Shot);
             }
+        }
+        if let Ok(mut weapon) = game.world.get::<&mut Weapon>(self.entity) {
+            *weapon = weapon_state;
+        }
 
-            if cont.normal.y < -0.9 {
-                is_on_ground = true;
+        self.animate(game, assets);
 
-                if let Some(ent) = game.hecs_sync.get_collider_entity(cont.colliders[1]) {
-                    tiles_touched.push(ent);
+        // drop through platforms stood on
+
+        if self.events.contains(&PlayerEvent::DroppedThrough) {
+            for &ent in &contacts.platforms_below {
+                if let Ok(mut platform) = game.world.get::<&mut OneWayPlatform>(ent) {
+                    platform.dropped_through = true;
                 }
             }
         }
 
+        for (ent, hit) in contacts.enemies_hit {
+            Enemy::hit(game, assets, ent, hit);
+        }
+
+        // break tiles walked on
+
+        for ent in contacts.tiles_touched {
+            if let Ok(mut tile) = game.world.get::<&mut BreakableTile>(ent) {
+                tile.is_breaking = true;
+            }
+        }
+    }
+
+    /// Step the animation and show its current frame,
+    /// colored depending on state and whether double jump is spent.
+    /// Called at the end of `tick`, and on its own while dead since `tick` skips everything then.
+    pub fn animate(&mut self, game: &mut sf::Game, assets: &super::Assets) {
         let Ok((pose, body_key, mesh)) = game
             .world
             .query_one_mut::<(&sf::Pose, &sf::BodyKey, &mut sf::MeshId)>(self.entity)
         else {
             return;
         };
+        let velocity = game
+            .physics
+            .entity_set
+            .get_body_mut(*body_key)
+            .map_or(sf::DVec2::zero(), |body| body.velocity.linear);
+
+        self.animator.update(self.state, &self.events, velocity);
+        let tint = match self.state {
+            MovementState::Dash { .. } => PlayerTint::Dashing,
+            MovementState::Knockback { .. } | MovementState::Dead => PlayerTint::DoublejumpSpent,
+            _ if !self.has_doublejump => PlayerTint::DoublejumpSpent,
+            _ => PlayerTint::Normal,
+        };
+        *mesh = assets.player_anim_mesh(self.animator.current(), tint, self.animator.frame());
 
-        if is_on_ground {
-            self.has_doublejump = true;
-            self.frames_since_on_ground = 0;
-        } else {
-            self.frames_since_on_ground += 1;
+        let pos = pose.translation.xy();
+        let [width, height] = self.animator.shape();
+        if let Ok(mut visor_pose) = game.world.get::<&mut sf::Pose>(self.visor) {
+            visor_pose.translation.x = pos.x + (self.facing * (width / 2. - VISOR_INSET)) as f32;
+            visor_pose.translation.y = pos.y + (height * VISOR_HEIGHT) as f32;
         }
-        let is_coyote_time = self.frames_since_on_ground < COYOTE_TIME_FRAMES;
-
-        // also shoot a spherecast down to check for oneway platforms
-        if let Some(hit_below) = game.physics.spherecast(
-            COLLIDER_WIDTH,
-            sf::Ray {
-                // getting the y coordinate right here
-                // so that we don't get stuck in the block
-                // and also won't fall through it is pretty fiddly,
-                // would be nice to have a better solution for this
-                // sf note: we could probably provide something like this out of the box,
-                // like a collider that only resolves collisions in a specific direction
-                start: sf::Vec2::new(pose.translation.x, pose.translation.y + 0.31).to_precision(),
-                dir: sf::math::UnitDVec2::new_unchecked(sf::DVec2::new(0., -1.)),
-                length: 5.,
-            },
-        ) {
-            if let Some(coll) = game.physics.entity_set.get_collider_mut(hit_below.collider) {
-                if coll.layer == crate::physics_layers::ONEWAY_INACTIVE {
-                    coll.layer = crate::physics_layers::ONEWAY_ACTIVE;
+    }
+
+    /// Check for being on the ground or against a wall,
+    /// and handle touching enemies and pickups.
+    fn check_contacts(&self, game: &mut sf::Game, conf: &MovementConfig) -> Option<ContactInfo> {
+        let Ok((&pose, &coll_key)) = game
+            .world
+            .query_one_mut::<(&sf::Pose, &sf::ColliderKey)>(self.entity)
+        else {
+            return None;
+        };
+
+        let mut info = ContactInfo::default();
+        for cont in game.physics.contacts_for_collider(coll_key) {
+            if let Some(ent) = game.hecs_sync.get_collider_entity(cont.colliders[1]) {
+                if let Ok((&WeaponPickup(upgrade),)) =
+                    game.world.query_one_mut::<(&WeaponPickup,)>(ent)
+                {
+                    info.picked_upgrade = Some(upgrade);
+                    game.world.despawn(ent).ok();
+                    continue;
+                }
+
+                if let Ok((_, proj_pose)) = game
+                    .world
+                    .query_one_mut::<(&EnemyProjectile, &sf::Pose)>(ent)
+                {
+                    // shot by an enemy, get knocked back away from the projectile
+                    info.knockback_vel = Some(if pose.translation.x < proj_pose.translation.x {
+                        sf::DVec2::new(-conf.knockback_speed, 0.)
+                    } else {
+                        sf::DVec2::new(conf.knockback_speed, 0.)
+                    });
+                    game.world.despawn(ent).ok();
+                    continue;
+                }
+
+                if let Ok((_, enemy_pose)) = game.world.query_one_mut::<(&Enemy, &sf::Pose)>(ent) {
+                    // there can be multiple contacts with the same enemy,
+                    // only react to the first
+                    let already_hit = info.enemies_hit.iter().any(|(hit_ent, _)| *hit_ent == ent);
+                    if !already_hit {
+                        if cont.normal.y > -0.75 {
+                            // hit from the side, get knocked back
+                            info.knockback_vel =
+                                Some(if pose.translation.x < enemy_pose.translation.x {
+                                    sf::DVec2::new(-conf.knockback_speed, 0.)
+                                } else {
+                                    sf::DVec2::new(conf.knockback_speed, 0.)
+                                });
+                            info.enemies_hit.push((ent, EnemyHit::Touched));
+                        } else {
+                            // on top of the enemy, bounce
+                            info.bounced_on_enemy = true;
+                            info.enemies_hit.push((ent, EnemyHit::Stomped));
+                        }
+                    }
+                    // enemies aren't ground or walls,
+                    // standing on one would reset the stomp combo right away
+                    continue;
+                }
+            }
+
+            if cont.normal.x.abs() > 0.9 {
+                // clouds are only solid from above and enemies hurt instead,
+                // everything else touched from the side is a wall
+                let is_wall = game
+                    .physics
+                    .entity_set
+                    .get_collider(cont.colliders[1])
+                    .is_some_and(|coll| {
+                        !matches!(
+                            coll.layer,
+                            physics_layers::ONEWAY_ACTIVE
+                                | physics_layers::ONEWAY_INACTIVE
+                                | physics_layers::ENEMY
+                        )
+                    });
+                if is_wall {
+                    info.wall_dir = Some(cont.normal.x.signum());
+                }
+            }
+
+            if cont.normal.y < -0.9 {
+                info.on_ground = true;
+
+                if let Some(ent) = game.hecs_sync.get_collider_entity(cont.colliders[1]) {
+                    info.tiles_touched.push(ent);
+                    if let Ok(true) = game.world.satisfies::<(&OneWayPlatform,)>(ent) {
+                        info.platforms_below.push(ent);
+                    } else {
+                        info.solid_below = true;
+                    }
+                } else {
+                    info.solid_below = true;
                 }
             }
         }
 
-        let body = game
-            .physics
-            .entity_set
-            .get_body_mut(*body_key)
-            .expect("Player body disappeared unexpectedly");
+        Some(info)
+    }
 
-        // controls
+    /// Run the movement state machine for one tick.
+    fn update_movement(
+        &mut self,
+        body: &mut sf::Body,
+        input: &PlayerInput,
+        contacts: &ContactInfo,
+        conf: &MovementConfig,
+    ) {
+        if contacts.on_ground {
+            self.has_doublejump = true;
+            self.has_dash = true;
+        }
+        if input.lr != 0. {
+            self.facing = input.lr.signum();
+        }
+        // checked before counting down so that a press exactly `jump_buffer_frames` ago still counts
+        let has_buffered_jump = self.jump_buffer_frames > 0;
+        self.jump_buffer_frames = self.jump_buffer_frames.saturating_sub(1);
+        self.dash_cooldown_frames = self.dash_cooldown_frames.saturating_sub(1);
 
-        if let Some(vel) = knockback_vel {
+        // getting hit overrides whatever else was happening
+        if let Some(vel) = contacts.knockback_vel {
             body.velocity.linear = vel;
-            self.knockback_frames = KNOCKBACK_FRAMES;
             self.has_doublejump = true;
+            self.transition(MovementState::Knockback {
+                frames_left: conf.knockback_frames,
+            });
+            self.events.push(PlayerEvent::Damaged);
         }
 
-        if bounced_on_enemy {
+        if contacts.bounced_on_enemy {
             self.has_doublejump = true;
-            body.velocity.linear.y = if jump_held {
-                ENEMY_BOUNCE_YSPEED_JUMP_HELD
+            body.velocity.linear.y = if input.jump_held {
+                conf.enemy_bounce_yspeed_jump_held
             } else {
-                ENEMY_BOUNCE_YSPEED_JUMP_RELEASED
+                conf.enemy_bounce_yspeed_jump_released
             };
+            self.events.push(PlayerEvent::BouncedOnEnemy);
         }
 
-        if self.knockback_frames > 0 {
-            self.knockback_frames -= 1;
-        } else {
-            body.velocity.linear.x = lr_input * MAX_XSPEED;
-
-            // jump
-            if jump_pressed && (is_coyote_time || self.has_doublejump) {
-                body.velocity.linear.y = JUMP_YSPEED;
-                self.holding_jump = true;
-                if !is_coyote_time {
-                    self.has_doublejump = false;
+        // states that override control for a while
+
+        match self.state {
+            MovementState::Dead => return,
+            MovementState::Knockback { frames_left } => {
+                if frames_left > 1 {
+                    self.transition(MovementState::Knockback {
+                        frames_left: frames_left - 1,
+                    });
+                } else {
+                    self.transition(Self::free_state(contacts));
                 }
+                return;
             }
-            // cut jump short when button released
-            if self.holding_jump && jump_released {
-                self.holding_jump = false;
-                if body.velocity.linear.y > 0. {
-                    body.velocity.linear.y *= 0.25;
+            MovementState::Dash { frames_left, dir } => {
+                // dashes move in a straight line without falling
+                body.velocity.linear.y = 0.;
+                if frames_left > 1 {
+                    self.transition(MovementState::Dash {
+                        frames_left: frames_left - 1,
+                        dir,
+                    });
+                } else {
+                    body.velocity.linear.x = input.lr * conf.max_xspeed;
+                    self.transition(Self::free_state(contacts));
                 }
+                return;
             }
+            _ => {}
         }
 
-        // change the mesh depending on whether double jump is spent
-        *mesh = if self.has_doublejump && self.knockback_frames == 0 {
-            assets.player_mesh
-        } else {
-            assets.player_mesh_doublejumped
-        };
-
-        // shoot/aim
-
-        if lr_input != 0. || tb_input != 0. {
-            self.aim_dir = sf::math::UnitDVec2::new_normalize(sf::DVec2::new(lr_input, tb_input));
-        }
-        if shoot_input {
-            let pose = sf::PoseBuilder::new()
-                .with_position(pose.translation.xy())
-                .build();
-            let body = sf::Body::new_kinematic();
-            let body = game.physics.entity_set.insert_body(body);
-            let mesh = assets.bullet_mesh;
-            let bullet = Bullet { dir: self.aim_dir };
-
-            game.world.spawn((pose, body, mesh, bullet));
+        // dash in the horizontal direction of aim
+        if input.dash && self.has_dash && self.dash_cooldown_frames == 0 {
+            let dir = if self.aim_dir.x != 0. {
+                self.aim_dir.x.signum()
+            } else {
+                self.facing
+            };
+            body.velocity.linear = sf::DVec2::new(dir * conf.dash_speed, 0.);
+            self.has_dash = false;
+            self.dash_cooldown_frames = conf.dash_cooldown_frames;
+            // a dash cancels the jump so releasing the button doesn't affect it
+            self.holding_jump = false;
+            self.transition(MovementState::Dash {
+                frames_left: conf.dash_frames,
+                dir,
+            });
+            return;
         }
 
-        // break tiles walked on
+        // free movement, figure out which state we're in based on contacts
 
-        for ent in tiles_touched {
-            if let Ok(mut tile) = game.world.get::<&mut BreakableTile>(ent) {
-                tile.is_breaking = true;
+        // only airborne wall contact counts for sliding and wall jumping
+        let wall_dir = contacts
+            .wall_dir
+            .filter(|_| conf.wall_jump_enabled && !contacts.on_ground);
+        let next_state = if contacts.on_ground {
+            MovementState::Grounded
+        } else if let Some(wall_dir) =
+            wall_dir.filter(|wall_dir| input.lr * wall_dir > 0. && body.velocity.linear.y <= 0.)
+        {
+            MovementState::WallSlide { wall_dir }
+        } else {
+            match self.state {
+                MovementState::Airborne {
+                    coyote_frames_left,
+                    control_lock_frames,
+                } => MovementState::Airborne {
+                    coyote_frames_left: coyote_frames_left.saturating_sub(1),
+                    control_lock_frames: control_lock_frames.saturating_sub(1),
+                },
+                // walked off a ledge
+                MovementState::Grounded => MovementState::Airborne {
+                    coyote_frames_left: conf.coyote_time_frames,
+                    control_lock_frames: 0,
+                },
+                _ => MovementState::Airborne {
+                    coyote_frames_left: 0,
+                    control_lock_frames: 0,
+                },
             }
-        }
-    }
-
-    pub fn move_camera(&self, game: &mut sf::Game, camera: &mut sf::Camera) {
-        let Ok((pose,)) = game.world.query_one_mut::<(&sf::Pose,)>(self.entity) else {
-            return;
         };
-
-        if pose.translation.y > camera.pose.translation.y {
-            camera.pose.translation.y = pose.translation.y;
+        self.transition(next_state);
+
+        match self.state {
+            MovementState::Airborne {
+                control_lock_frames,
+                ..
+            } if control_lock_frames > 0 => {}
+            _ => body.velocity.linear.x = input.lr * conf.max_xspeed,
         }
-    }
-}
 
-/// Check for bullets colliding with tiles and set the tiles to break.
-pub fn handle_bullets(game: &mut sf::Game, camera: &sf::Camera) {
-    // gather hits first so that we don't need tricky nested query shenanigans
-    let mut hits: Vec<(sf::hecs::Entity, sf::hecs::Entity)> = Vec::new();
-    // also destroy bullets that have left the area visible on camera
-    let mut off_cameras: Vec<sf::hecs::Entity> = Vec::new();
-    for (bullet_ent, (bullet, pose)) in game.world.query_mut::<(&Bullet, &mut sf::Pose)>() {
-        // check for the next thing hit by doing a spherecast
-        // so that we don't end up hitting multiple things at the same time.
-        // sf note: in some cases we'll need to filter cast results by collision layer.
-        // it's not essential here though because the player is the only thing that shouldn't be hit
-        // sf note 2: it would be nice if we could return everything hit along the ray
-        // instead of just the nearest thing
-        // (currently enemies can be missed by this if they're inside a cloud)
-        let next_hit = game.physics.spherecast(
-            BULLET_RADIUS,
-            sf::Ray {
-                start: pose.translation.xy().to_precision(),
-                dir: bullet.dir,
-                // add a bit of extra distance to avoid tunneling
-                length: BULLET_SPEED * game.dt_fixed + 0.05,
-            },
-        );
-        if let Some(ent) = next_hit.and_then(|hit| game.hecs_sync.get_collider_entity(hit.collider))
-        {
-            hits.push((bullet_ent, ent));
+        // slide down slowly when pushing against a wall
+        if let MovementState::WallSlide { .. } = self.state {
+            if body.velocity.linear.y < -conf.wall_slide_speed {
+                body.velocity.linear.y = -conf.wall_slide_speed;
+            }
         }
 
-        let step = BULLET_SPEED * game.dt_fixed * *bullet.dir;
-        pose.translation.x += step.x as f32;
-        pose.translation.y += step.y as f32;
-
-        if camera
-            .point_world_to_screen(pose.translation.xy())
-            .is_none()
+        // jump.
+        // a fresh press can use the double jump,
+        // but a buffered one only fires from the ground
+        // so that it doesn't eat the double jump a few frames late
+        let is_coyote_time = self.state.is_coyote_time();
+        let is_buffered_jump = !input.jump_pressed && has_buffered_jump;
+        let can_drop_through = self.state == MovementState::Grounded
+            && !contacts.platforms_below.is_empty()
+            && !contacts.solid_below;
+        if input.jump_pressed && input.tb < 0. && can_drop_through {
+            // down + jump drops through one-way platforms instead of jumping.
+            // the platforms themselves are made passable at the end of the tick
+            // since we don't have access to them here
+            body.velocity.linear.y = -1.;
+            self.jump_buffer_frames = 0;
+            self.transition(MovementState::Airborne {
+                coyote_frames_left: 0,
+                control_lock_frames: 0,
+            });
+            self.events.push(PlayerEvent::DroppedThrough);
+        } else if let Some(wall_dir) = wall_dir.filter(|_| input.jump_pressed) {
+            // wall jump takes priority over the double jump
+            // and kicks away from the wall
+            body.velocity.linear =
+                sf::DVec2::new(-wall_dir * conf.wall_jump_xspeed, conf.wall_jump_yspeed);
+            self.holding_jump = true;
+            self.jump_buffer_frames = 0;
+            self.transition(MovementState::Airborne {
+                coyote_frames_left: 0,
+                control_lock_frames: conf.wall_jump_control_lock_frames,
+            });
+            self.events.push(PlayerEvent::WallJumped);
+        } else if (input.jump_pressed && (is_coyote_time || self.has_doublejump))
+            || (is_buffered_jump && is_coyote_time)
         {
-            off_cameras.push(bullet_ent);
+            body.velocity.linear.y = conf.jump_yspeed;
+            // a buffered jump may fire after the button was already released,
+            // in which case it's cut short right away
+            self.holding_jump = !is_buffered_jump || input.jump_held;
+            if !self.holding_jump {
+                body.velocity.linear.y *= 0.25;
+            }
+            self.jump_buffer_frames = 0;
+            if is_coyote_time {
+                self.events.push(PlayerEvent::Jumped);
+            } else {
+                self.has_doublejump = false;
+                self.events.push(PlayerEvent::DoubleJumped);
+            }
+            self.transition(MovementState::Airborne {
+                coyote_frames_left: 0,
+                control_lock_frames: 0,
+            });
+        } else if input.jump_pressed {
+            self.jump_buffer_frames = conf.jump_buffer_frames;
+        }
+        // cut jump short when button released
+        if self.holding_jump && input.jump_released {
+            self.holding_jump = false;
+            if body.velocity.linear.y > 0. {
+                body.velocity.linear.y *= 0.25;
+            }
         }
     }
 
-    for (bullet, other) in hits {
-        if let Ok((tile,)) = game.world.query_one_mut::<(&mut BreakableTile,)>(other) {
-            tile.is_breaking = true;
-            tile.time_to_break -= BULLET_TILE_DAMAGE;
-            if tile.blocks_bullets {
-                game.world.despawn(bullet).ok();
+    /// State to return to after something that overrides control ends.
+    fn free_state(contacts: &ContactInfo) -> MovementState {
+        if contacts.on_ground {
+            MovementState::Grounded
+        } else {
+            MovementState::Airborne {
+                coyote_frames_left: 0,
+                control_lock_frames: 0,
             }
-        } else if let Ok(true) = game.world.satisfies::<(&mut Enemy,)>(other) {
-            // hitting an enemy destroys both the enemy and the bullet
-            // (maybe eventually there could be enemies with HP but not yet)
-            game.world.despawn(other).ok();
-            game.world.despawn(bullet).ok();
         }
     }
+}
 
-    for bullet in off_cameras {
-        game.world.despawn(bullet).ok();
+impl PlayerInput {
+    fn read(game: &sf::Game) -> Self {
+        // TODO configurable keys, gamepad support
+        let lr = game.input.axis(sf::AxisQuery {
+            pos_btn: sf::Key::ArrowRight.into(),
+            neg_btn: sf::Key::ArrowLeft.into(),
+        });
+        let tb = game.input.axis(sf::AxisQuery {
+            pos_btn: sf::Key::ArrowUp.into(),
+            neg_btn: sf::Key::ArrowDown.into(),
+        });
+        let jump_btn = sf::ButtonQuery::kb(sf::Key::ShiftLeft);
+
+        Self {
+            lr,
+            tb,
+            jump_pressed: game.input.button(jump_btn),
+            jump_held: game.input.button(jump_btn.held()),
+            jump_released: game.input.button(jump_btn.released()),
+            // hold to keep shooting, the weapon's fire interval limits the rate
+            shoot: game.input.button(sf::ButtonQuery::kb(sf::Key::KeyZ).held()),
+            dash: game.input.button(sf::ButtonQuery::kb(sf::Key::KeyX)),
+        }
     }
 }
@@ -0,0 +1,165 @@
+use starframe as sf;
+
+use super::{MovementState, PlayerEvent};
+
+/// How long the shooting animation plays after a shot.
+const SHOOT_ANIM_FRAMES: u32 = 12;
+/// Horizontal speed below which the player counts as standing still.
+const RUN_SPEED_THRESHOLD: f64 = 0.1;
+
+/// Animations the player can show, chosen every tick from the movement state.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum PlayerAnim {
+    Idle,
+    Run,
+    Jump,
+    Fall,
+    Shoot,
+    Hurt,
+    Dash,
+}
+
+/// Color the player is drawn in, showing whether double jump is spent.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum PlayerTint {
+    Normal,
+    DoublejumpSpent,
+    Dashing,
+}
+
+/// Sequence of frames played in a loop.
+///
+/// sf note: there's no player art in the model file yet,
+/// so animations squash and stretch the placeholder body instead of playing sprites.
+/// each frame is a separate mesh built from these sizes in `Assets`
+pub struct Clip {
+    /// width and height of the body in each frame
+    pub shapes: &'static [[f64; 2]],
+    /// ticks each frame is shown for
+    pub frame_ticks: u32,
+}
+
+impl PlayerAnim {
+    pub const ALL: [Self; 7] = [
+        Self::Idle,
+        Self::Run,
+        Self::Jump,
+        Self::Fall,
+        Self::Shoot,
+        Self::Hurt,
+        Self::Dash,
+    ];
+
+    pub fn clip(&self) -> Clip {
+        match self {
+            Self::Idle => Clip {
+                shapes: &[[0.8, 1.], [0.82, 0.97]],
+                frame_ticks: 30,
+            },
+            Self::Run => Clip {
+                shapes: &[[0.8, 1.], [0.84, 0.95], [0.8, 1.], [0.76, 1.04]],
+                frame_ticks: 6,
+            },
+            Self::Jump => Clip {
+                shapes: &[[0.7, 1.1]],
+                frame_ticks: 1,
+            },
+            Self::Fall => Clip {
+                shapes: &[[0.76, 1.04]],
+                frame_ticks: 1,
+            },
+            Self::Shoot => Clip {
+                shapes: &[[0.86, 0.94], [0.82, 0.98]],
+                frame_ticks: 6,
+            },
+            Self::Hurt => Clip {
+                shapes: &[[0.9, 0.9], [0.7, 1.05]],
+                frame_ticks: 4,
+            },
+            Self::Dash => Clip {
+                shapes: &[[1., 0.8]],
+                frame_ticks: 1,
+            },
+        }
+    }
+}
+
+impl PlayerTint {
+    pub const ALL: [Self; 3] = [Self::Normal, Self::DoublejumpSpent, Self::Dashing];
+}
+
+/// Picks the animation to show based on what the player is doing
+/// and steps through its frames.
+pub struct PlayerAnimator {
+    current: PlayerAnim,
+    frame: usize,
+    // ticks the current frame has been shown for
+    frame_ticks: u32,
+    shoot_frames_left: u32,
+}
+
+impl Default for PlayerAnimator {
+    fn default() -> Self {
+        Self {
+            current: PlayerAnim::Idle,
+            frame: 0,
+            frame_ticks: 0,
+            shoot_frames_left: 0,
+        }
+    }
+}
+
+impl PlayerAnimator {
+    pub fn current(&self) -> PlayerAnim {
+        self.current
+    }
+
+    /// Index of the frame of the current animation being shown.
+    pub fn frame(&self) -> usize {
+        self.frame
+    }
+
+    /// Width and height of the body in the frame being shown.
+    pub fn shape(&self) -> [f64; 2] {
+        self.current.clip().shapes[self.frame]
+    }
+
+    /// Choose the animation for this tick and advance it by one tick.
+    /// Call once per update after moving the player.
+    pub fn update(&mut self, state: MovementState, events: &[PlayerEvent], velocity: sf::DVec2) {
+        self.shoot_frames_left = self.shoot_frames_left.saturating_sub(1);
+        if events.contains(&PlayerEvent::Shot) {
+            self.shoot_frames_left = SHOOT_ANIM_FRAMES;
+        }
+
+        let next = match state {
+            MovementState::Knockback { .. } | MovementState::Dead => PlayerAnim::Hurt,
+            MovementState::Dash { .. } => PlayerAnim::Dash,
+            // shooting only has its own animation on the ground,
+            // in the air the jump and fall poses take priority
+            MovementState::Grounded if self.shoot_frames_left > 0 => PlayerAnim::Shoot,
+            MovementState::Grounded if velocity.x.abs() > RUN_SPEED_THRESHOLD => PlayerAnim::Run,
+            MovementState::Grounded => PlayerAnim::Idle,
+            MovementState::Airborne { .. } | MovementState::WallSlide { .. } => {
+                if velocity.y > 0. {
+                    PlayerAnim::Jump
+                } else {
+                    PlayerAnim::Fall
+                }
+            }
+        };
+
+        if next != self.current {
+            self.current = next;
+            self.frame = 0;
+            self.frame_ticks = 0;
+            return;
+        }
+        let clip = self.current.clip();
+        self.frame_ticks += 1;
+        if self.frame_ticks >= clip.frame_ticks {
+            self.frame_ticks = 0;
+            self.frame = (self.frame + 1) % clip.shapes.len();
+        }
+    }
+}
@@ -0,0 +1,64 @@
+/// What the player is currently doing.
+/// Only one of these is active at a time
+/// and it determines how input and contacts are handled.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum MovementState {
+    /// standing on something solid
+    Grounded,
+    /// in the air by jumping or falling
+    Airborne {
+        /// frames left to still jump as if on the ground after walking off a ledge
+        coyote_frames_left: u32,
+        /// frames left where horizontal input is ignored.
+        /// set by wall jumps so that the kick away from the wall isn't immediately cancelled
+        control_lock_frames: u32,
+    },
+    /// pushing against a wall while falling, which slows the fall
+    WallSlide {
+        /// direction of the wall, -1 for left and 1 for right
+        wall_dir: f64,
+    },
+    /// hit by an enemy and flying away from it without control
+    Knockback { frames_left: usize },
+    /// moving horizontally at high speed without falling
+    Dash { frames_left: usize, dir: f64 },
+    /// hit by the spike roller, no longer responds to anything
+    Dead,
+}
+
+/// Something that happened to the player during a tick.
+/// These are collected every tick so that animations, sounds etc.
+/// can react to them without digging into the movement logic.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum PlayerEvent {
+    StateChanged {
+        from: MovementState,
+        to: MovementState,
+    },
+    Jumped,
+    DoubleJumped,
+    WallJumped,
+    DroppedThrough,
+    BouncedOnEnemy,
+    Shot,
+    /// hit by an enemy or projectile, even if already being knocked back
+    Damaged,
+}
+
+impl MovementState {
+    /// Whether two states are the same kind of state,
+    /// ignoring the timers and such inside them.
+    pub fn same_kind(&self, other: &Self) -> bool {
+        std::mem::discriminant(self) == std::mem::discriminant(other)
+    }
+
+    pub fn is_coyote_time(&self) -> bool {
+        match self {
+            Self::Grounded => true,
+            Self::Airborne {
+                coyote_frames_left, ..
+            } => *coyote_frames_left > 0,
+            _ => false,
+        }
+    }
+}
@@ -0,0 +1,173 @@
+use starframe as sf;
+
+use crate::{
+    level::{self, VIEW_HEIGHT},
+    player::{MovementState, PlayerEvent, PlayerState},
+    viewport::VIEW_WIDTH,
+};
+
+/// Score for the first bounce of a combo, multiplied by the combo count for later bounces.
+const COMBO_BASE_SCORE: u64 = 100;
+/// Most pips shown above the player, longer combos still count but don't show more.
+const MAX_COMBO_PIPS: usize = 10;
+const PIP_SPACING: f32 = 0.25;
+/// Height of the pips above the center of the player.
+const PIP_HEIGHT: f32 = 1.;
+/// Digits in the score display, higher scores show as all nines.
+const SCORE_DIGITS: usize = 8;
+/// Length of one segment of a digit in the score display.
+pub const SEGMENT_LEN: f32 = 0.3;
+pub const SEGMENT_THICKNESS: f32 = 0.06;
+const DIGIT_SPACING: f32 = 0.5;
+/// Distance of the score display from the top left corner of the screen.
+const SCORE_MARGIN: f32 = 0.6;
+
+// sf note: there's no text rendering in starframe yet,
+// so the score is drawn as seven-segment digits made of individual segment meshes.
+// segments are in the usual order from a to g
+// (top, top right, bottom right, bottom, bottom left, top left, middle),
+// given as offsets from the center of the digit in segment lengths
+// and whether they're vertical
+const SEGMENTS: [([f32; 2], bool); 7] = [
+    ([0., 1.], false),
+    ([0.5, 0.5], true),
+    ([0.5, -0.5], true),
+    ([0., -1.], false),
+    ([-0.5, -0.5], true),
+    ([-0.5, 0.5], true),
+    ([0., 0.], false),
+];
+// segments lit for each digit from 0 to 9, bit n is segment n of the above
+const DIGIT_SEGMENTS: [u8; 10] = [
+    0b0111111, 0b0000110, 0b1011011, 0b1001111, 0b1100110, 0b1101101, 0b1111101, 0b0000111,
+    0b1111111, 0b1101111,
+];
+
+/// Keeps track of the score and combos of bouncing on enemies
+/// without touching the ground in between.
+/// The current combo is shown as a row of pips above the player
+/// and the score in the top left corner of the screen.
+pub struct Score {
+    pub score: u64,
+    pub combo: u32,
+    pips: Vec<sf::hecs::Entity>,
+    // segment entities of each digit of the score display, most significant digit first
+    digits: Vec<[sf::hecs::Entity; 7]>,
+}
+
+impl Score {
+    pub fn spawn(game: &mut sf::Game, assets: &crate::Assets) -> Self {
+        let pips = (0..MAX_COMBO_PIPS)
+            .map(|_| {
+                let pose = sf::PoseBuilder::new()
+                    .with_position(level::PARKING_POS)
+                    .with_depth(-10.)
+                    .build();
+                game.world.spawn((pose, assets.combo_pip_mesh))
+            })
+            .collect();
+
+        let digits = (0..SCORE_DIGITS)
+            .map(|_| {
+                std::array::from_fn(|seg_idx| {
+                    let pose = sf::PoseBuilder::new()
+                        .with_position(level::PARKING_POS)
+                        .with_depth(-10.)
+                        .build();
+                    let mesh = if SEGMENTS[seg_idx].1 {
+                        assets.score_segment_vertical_mesh
+                    } else {
+                        assets.score_segment_mesh
+                    };
+                    game.world.spawn((pose, mesh))
+                })
+            })
+            .collect();
+
+        Self {
+            score: 0,
+            combo: 0,
+            pips,
+            digits,
+        }
+    }
+
+    pub fn add(&mut self, points: u64) {
+        self.score += points;
+    }
+
+    /// Count bounces from the player's events this tick and move the combo pips.
+    /// Call once per update after ticking the player.
+    pub fn tick(&mut self, game: &mut sf::Game, player: &PlayerState) {
+        for ev in player.events() {
+            match ev {
+                PlayerEvent::BouncedOnEnemy => {
+                    self.combo += 1;
+                    self.add(COMBO_BASE_SCORE * self.combo as u64);
+                }
+                PlayerEvent::StateChanged {
+                    to: MovementState::Grounded,
+                    ..
+                } => {
+                    self.combo = 0;
+                }
+                _ => {}
+            }
+        }
+
+        let player_pos = game
+            .world
+            .get::<&sf::Pose>(player.entity)
+            .map(|pose| pose.translation.xy())
+            .ok();
+        let shown = (self.combo as usize).min(MAX_COMBO_PIPS);
+        let first_x = -((shown.max(1) - 1) as f32) * PIP_SPACING / 2.;
+        for (idx, &pip) in self.pips.iter().enumerate() {
+            let Ok(mut pose) = game.world.get::<&mut sf::Pose>(pip) else {
+                continue;
+            };
+            match player_pos {
+                Some(player_pos) if idx < shown => {
+                    pose.translation.x = player_pos.x + first_x + idx as f32 * PIP_SPACING;
+                    pose.translation.y = player_pos.y + PIP_HEIGHT;
+                }
+                _ => level::park(&mut pose),
+            }
+        }
+    }
+
+    /// Show the score in the top left corner of the screen.
+    /// Call once per update after moving the camera.
+    pub fn update_display(&self, game: &mut sf::Game, camera: &sf::Camera) {
+        let max_score = 10_u64.pow(SCORE_DIGITS as u32) - 1;
+        let score_digits: Vec<usize> = self
+            .score
+            .min(max_score)
+            .to_string()
+            .bytes()
+            .map(|digit| (digit - b'0') as usize)
+            .collect();
+
+        let first_x = camera.pose.translation.x - VIEW_WIDTH / 2. + SCORE_MARGIN;
+        let center_y = camera.pose.translation.y + VIEW_HEIGHT / 2. - SCORE_MARGIN - SEGMENT_LEN;
+        for (digit_idx, segments) in self.digits.iter().enumerate() {
+            let lit = score_digits
+                .get(digit_idx)
+                .map(|&digit| DIGIT_SEGMENTS[digit])
+                .unwrap_or(0);
+            let center_x = first_x + digit_idx as f32 * DIGIT_SPACING;
+            for (seg_idx, &seg) in segments.iter().enumerate() {
+                let Ok(mut pose) = game.world.get::<&mut sf::Pose>(seg) else {
+                    continue;
+                };
+                if lit & (1 << seg_idx) != 0 {
+                    let [offset_x, offset_y] = SEGMENTS[seg_idx].0;
+                    pose.translation.x = center_x + offset_x * SEGMENT_LEN;
+                    pose.translation.y = center_y + offset_y * SEGMENT_LEN;
+                } else {
+                    level::park(&mut pose);
+                }
+            }
+        }
+    }
+}
@@ -1,6 +1,12 @@
 use starframe as sf;
 
-use crate::{level::tile::BreakableTile, player::PlayerState};
+use crate::{
+    enemy::{Enemy, RollerReaction},
+    level::tile::BreakableTile,
+    player::PlayerState,
+    score::Score,
+    Assets,
+};
 
 pub struct SpikeRoller {
     entity: sf::hecs::Entity,
@@ -33,11 +39,19 @@ impl SpikeRoller {
         Self { entity }
     }
 
+    /// Height of the top of the roller.
+    pub fn height(&self, game: &sf::Game) -> Option<f32> {
+        let pose = game.world.get::<&sf::Pose>(self.entity).ok()?;
+        Some(pose.translation.y + 0.5)
+    }
+
     pub fn tick(
         &self,
         game: &mut sf::Game,
+        assets: &Assets,
         camera: &sf::Camera,
         player: &PlayerState,
+        score: &mut Score,
     ) -> TickResult {
         let Ok((pose, coll)) = game
             .world
@@ -53,8 +67,9 @@ impl SpikeRoller {
             // (probably won't have time for this)
         }
 
-        // check for collisions with tiles and player, destroy them
-
+        // check for collisions with tiles and player, destroy them.
+        // enemies are gathered into a buffer since killing them needs the whole game
+        let mut enemies_hit: Vec<sf::hecs::Entity> = Vec::new();
         for cont in game.physics.contacts_for_collider(*coll) {
             let Some(ent) = game.hecs_sync.get_collider_entity(cont.colliders[1]) else {
                 continue;
@@ -65,12 +80,30 @@ impl SpikeRoller {
                 tile.time_to_break = tile.time_to_break.min(0.25);
             } else if ent == player.entity {
                 return TickResult { player_hit: true };
+            } else if let Ok(true) = game.world.satisfies::<(&Enemy,)>(ent) {
+                enemies_hit.push(ent);
             } else {
                 // all entities besides the player and tiles are just destroyed
                 game.world.despawn(ent).ok();
             }
         }
 
+        for ent in enemies_hit {
+            let Ok(reaction) = game
+                .world
+                .get::<&Enemy>(ent)
+                .map(|enemy| enemy.ty.roller_reaction)
+            else {
+                continue;
+            };
+            match reaction {
+                RollerReaction::Ignore => continue,
+                RollerReaction::Shred { score: points } => score.add(points),
+                RollerReaction::Flee => {}
+            }
+            Enemy::kill(game, assets, ent);
+        }
+
         TickResult::default()
     }
 }
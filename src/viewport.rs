@@ -0,0 +1,94 @@
+use starframe as sf;
+
+use crate::level::VIEW_HEIGHT;
+
+/// Aspect ratio of the area the game is played in.
+/// Windows of any other shape get black bars on the sides or top and bottom.
+pub const ASPECT_RATIO: f32 = 16. / 9.;
+/// Width seen on camera, including a bit of the side walls on both sides.
+pub const VIEW_WIDTH: f32 = VIEW_HEIGHT * ASPECT_RATIO;
+/// Smallest resolution the game is meant to be played at,
+/// used as the base for integer scaling.
+const BASE_RESOLUTION: [u32; 2] = [640, 360];
+/// Size of the meshes used as bars.
+/// They only need to reach past the edges of the window at any reasonable size.
+pub const BAR_SIZE: f32 = 1000.;
+const BAR_DEPTH: f32 = -100.;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum ScaleMode {
+    /// scale the game area to fill as much of the window as possible
+    Fit,
+    /// scale the game area only by whole multiples of `BASE_RESOLUTION`
+    /// for crisp pixels at the cost of larger bars
+    Integer,
+}
+
+/// Keeps the whole width of the level on screen
+/// by fixing the aspect ratio and covering the rest of the window with bars.
+///
+/// sf note: bars are implemented as big black meshes in the world
+/// that follow the camera around. it would be better to set a viewport
+/// on the renderer and let the clear color handle the rest
+pub struct Viewport {
+    pub scale_mode: ScaleMode,
+    // left, right, bottom, top
+    bars: [sf::hecs::Entity; 4],
+}
+
+impl Viewport {
+    pub fn spawn(game: &mut sf::Game, assets: &crate::Assets, scale_mode: ScaleMode) -> Self {
+        let bars = [(); 4].map(|_| {
+            let pose = sf::PoseBuilder::new().with_depth(BAR_DEPTH).build();
+            game.world.spawn((pose, assets.letterbox_mesh))
+        });
+        Self { scale_mode, bars }
+    }
+
+    pub fn toggle_scale_mode(&mut self) {
+        self.scale_mode = match self.scale_mode {
+            ScaleMode::Fit => ScaleMode::Integer,
+            ScaleMode::Integer => ScaleMode::Fit,
+        };
+    }
+
+    /// Size the camera to the window and move the bars to the edges of the game area.
+    /// Call right before drawing, after the camera has been moved for the frame.
+    pub fn update(&self, game: &mut sf::Game, camera: &mut sf::Camera) {
+        let window_size = game.renderer.window().inner_size();
+        let window_w = window_size.width.max(1) as f32;
+        let window_h = window_size.height.max(1) as f32;
+
+        // size of the game area in pixels
+        let area_h = match self.scale_mode {
+            ScaleMode::Fit => window_h.min(window_w / ASPECT_RATIO),
+            ScaleMode::Integer => {
+                let scale = (window_size.width / BASE_RESOLUTION[0])
+                    .min(window_size.height / BASE_RESOLUTION[1])
+                    .max(1);
+                (scale * BASE_RESOLUTION[1]) as f32
+            }
+        };
+
+        // make the camera cover the entire window
+        // so that the game area ends up exactly the size computed above
+        let units_per_pixel = VIEW_HEIGHT / area_h;
+        camera.view_width = window_w * units_per_pixel;
+        camera.view_height = window_h * units_per_pixel;
+
+        let center = camera.pose.translation.xy();
+        let half_bar = BAR_SIZE / 2.;
+        let offsets = [
+            [-(VIEW_WIDTH / 2. + half_bar), 0.],
+            [VIEW_WIDTH / 2. + half_bar, 0.],
+            [0., -(VIEW_HEIGHT / 2. + half_bar)],
+            [0., VIEW_HEIGHT / 2. + half_bar],
+        ];
+        for (bar, offset) in self.bars.iter().zip(offsets) {
+            if let Ok((pose,)) = game.world.query_one_mut::<(&mut sf::Pose,)>(*bar) {
+                pose.translation.x = center.x + offset[0];
+                pose.translation.y = center.y + offset[1];
+            }
+        }
+    }
+}
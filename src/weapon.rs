@@ -0,0 +1,383 @@
+use rand::Rng;
+use starframe as sf;
+
+use sf::math::ConvertPrecision;
+
+use crate::{
+    enemy::{turret::EnemyProjectile, Enemy, EnemyHit},
+    level::{self, tile::BreakableTile},
+    physics_layers,
+    physics_query::{self, LayerMask},
+    Assets,
+};
+
+/// Properties of the bullets fired by a weapon and how often they can be fired.
+#[derive(Clone, Copy, Debug)]
+pub struct WeaponStats {
+    /// minimum number of frames between shots
+    pub fire_interval: u32,
+    pub bullet_speed: f64,
+    pub bullet_radius: f64,
+    /// number of extra things a bullet can go through before it's destroyed
+    pub pierce: u32,
+    /// damage dealt to tiles, in seconds of their breaking time
+    pub damage: f32,
+    /// number of bullets fired per shot,
+    /// fanned out evenly over `spread_angle` (in radians)
+    pub bullets_per_shot: u32,
+    pub spread_angle: f64,
+}
+
+impl Default for WeaponStats {
+    fn default() -> Self {
+        Self {
+            fire_interval: 8,
+            bullet_speed: 25.,
+            bullet_radius: 0.35,
+            pierce: 0,
+            damage: 0.5,
+            bullets_per_shot: 1,
+            spread_angle: 0.,
+        }
+    }
+}
+
+/// Weapon component attached to the player entity.
+#[derive(Clone, Copy, Debug, Default)]
+pub struct Weapon {
+    pub stats: WeaponStats,
+    /// frames left until the weapon can fire again
+    cooldown: u32,
+    /// shots left before an upgrade wears off.
+    /// None means the weapon is in its base state with unlimited ammo
+    ammo: Option<u32>,
+}
+
+/// Modification to a weapon given by picking up a `WeaponPickup`.
+#[derive(Clone, Copy, Debug)]
+pub enum WeaponUpgrade {
+    SpreadShot,
+    PiercingShot,
+    RapidFire,
+}
+
+/// Component for a collectible that upgrades the player's weapon on touch.
+#[derive(Clone, Copy, Debug)]
+pub struct WeaponPickup(pub WeaponUpgrade);
+
+/// Maximum number of player bullets that can exist at once.
+/// If more are fired, the oldest ones get reused.
+const BULLET_POOL_CAPACITY: usize = 64;
+
+pub struct Bullet {
+    // bullets not currently flying are parked outside the level and skipped in updates
+    active: bool,
+    // bullets store their movement direction and move manually
+    // in order to ensure they don't tunnel and hit things in the right order
+    dir: sf::math::UnitDVec2,
+    speed: f64,
+    radius: f64,
+    pierce_left: u32,
+    damage: f32,
+    // things already pierced,
+    // remembered so that we don't hit them again on the following frames
+    pierced: Vec<sf::hecs::Entity>,
+}
+
+/// Fixed set of bullet entities that are reused
+/// instead of spawning new entities and bodies for every shot.
+pub struct BulletPool {
+    bullets: Vec<sf::hecs::Entity>,
+    // bullets are handed out in a ring,
+    // so this is also the oldest one when all of them are in use
+    next: usize,
+}
+
+impl Weapon {
+    /// Step the cooldown forward by one frame. Call once per update.
+    pub fn tick(&mut self) {
+        self.cooldown = self.cooldown.saturating_sub(1);
+    }
+
+    pub fn apply_upgrade(&mut self, upgrade: WeaponUpgrade) {
+        // upgrades don't stack, picking up a new one replaces the old
+        let mut stats = WeaponStats::default();
+        match upgrade {
+            WeaponUpgrade::SpreadShot => {
+                stats.bullets_per_shot = 3;
+                stats.spread_angle = 0.5;
+            }
+            WeaponUpgrade::PiercingShot => {
+                stats.pierce = 2;
+                stats.bullet_speed = 35.;
+            }
+            WeaponUpgrade::RapidFire => {
+                stats.fire_interval = 3;
+                stats.damage = 0.35;
+            }
+        }
+        self.stats = stats;
+        self.ammo = Some(upgrade.ammo());
+    }
+
+    /// Fire the weapon in the given direction if it's off cooldown.
+    /// Returns whether a shot was actually fired.
+    pub fn fire(
+        &mut self,
+        game: &mut sf::Game,
+        bullets: &mut BulletPool,
+        pos: sf::Vec2,
+        dir: sf::math::UnitDVec2,
+    ) -> bool {
+        if self.cooldown > 0 {
+            return false;
+        }
+        self.cooldown = self.stats.fire_interval;
+
+        let count = self.stats.bullets_per_shot.max(1);
+        for bullet_idx in 0..count {
+            let angle = if count == 1 {
+                0.
+            } else {
+                self.stats.spread_angle * (bullet_idx as f64 / (count - 1) as f64 - 0.5)
+            };
+            let (sin, cos) = angle.sin_cos();
+            let dir = sf::math::UnitDVec2::new_normalize(sf::DVec2::new(
+                dir.x * cos - dir.y * sin,
+                dir.x * sin + dir.y * cos,
+            ));
+
+            bullets.fire(game, pos, dir, &self.stats);
+        }
+
+        if let Some(ammo) = &mut self.ammo {
+            *ammo = ammo.saturating_sub(1);
+            if *ammo == 0 {
+                *self = Self {
+                    cooldown: self.cooldown,
+                    ..Self::default()
+                };
+            }
+        }
+
+        true
+    }
+}
+
+impl Bullet {
+    fn inactive() -> Self {
+        Self {
+            active: false,
+            dir: sf::math::UnitDVec2::unit_x(),
+            speed: 0.,
+            radius: 0.,
+            pierce_left: 0,
+            damage: 0.,
+            pierced: Vec::new(),
+        }
+    }
+}
+
+impl BulletPool {
+    /// Spawn all the bullet entities this pool manages.
+    /// These are removed by `World::clear` like everything else,
+    /// so the pool needs to be spawned again after clearing.
+    pub fn spawn(game: &mut sf::Game, assets: &Assets) -> Self {
+        let bullets = (0..BULLET_POOL_CAPACITY)
+            .map(|_| {
+                let pose = sf::PoseBuilder::new()
+                    .with_position(level::PARKING_POS)
+                    .build();
+                let body = sf::Body::new_kinematic();
+                let body = game.physics.entity_set.insert_body(body);
+                let mesh = assets.bullet_mesh;
+                game.world.spawn((pose, body, mesh, Bullet::inactive()))
+            })
+            .collect();
+
+        Self { bullets, next: 0 }
+    }
+
+    fn fire(
+        &mut self,
+        game: &mut sf::Game,
+        pos: sf::Vec2,
+        dir: sf::math::UnitDVec2,
+        stats: &WeaponStats,
+    ) {
+        let ent = self.bullets[self.next];
+        self.next = (self.next + 1) % self.bullets.len();
+
+        let Ok((bullet, pose)) = game
+            .world
+            .query_one_mut::<(&mut Bullet, &mut sf::Pose)>(ent)
+        else {
+            return;
+        };
+        bullet.active = true;
+        bullet.dir = dir;
+        bullet.speed = stats.bullet_speed;
+        bullet.radius = stats.bullet_radius;
+        bullet.pierce_left = stats.pierce;
+        bullet.damage = stats.damage;
+        bullet.pierced.clear();
+        pose.translation.x = pos.x;
+        pose.translation.y = pos.y;
+    }
+}
+
+/// Return a bullet to its pool to be reused by a later shot.
+fn deactivate_bullet(game: &mut sf::Game, ent: sf::hecs::Entity) {
+    let Ok((bullet, pose)) = game
+        .world
+        .query_one_mut::<(&mut Bullet, &mut sf::Pose)>(ent)
+    else {
+        return;
+    };
+    bullet.active = false;
+    level::park(pose);
+}
+
+impl WeaponUpgrade {
+    pub fn random() -> Self {
+        match rand::thread_rng().gen_range(0..3) {
+            0 => Self::SpreadShot,
+            1 => Self::PiercingShot,
+            _ => Self::RapidFire,
+        }
+    }
+
+    /// Number of shots an upgrade lasts for.
+    pub fn ammo(&self) -> u32 {
+        match self {
+            Self::SpreadShot => 30,
+            Self::PiercingShot => 30,
+            Self::RapidFire => 100,
+        }
+    }
+
+    pub fn spawn_pickup(self, game: &mut sf::Game, assets: &Assets, pos: sf::Vec2) {
+        let pose = sf::PoseBuilder::new().with_position(pos).build();
+        let coll = sf::Collider::new_circle(0.3)
+            .with_layer(crate::physics_layers::PICKUP)
+            .sensor();
+        let coll = game.physics.entity_set.insert_collider(coll);
+        let mesh = assets.pickup_mesh;
+
+        game.world.spawn((WeaponPickup(self), pose, coll, mesh));
+    }
+}
+
+/// Layers that bullets interact with.
+const BULLET_HITS: LayerMask = LayerMask::all().without(&[
+    physics_layers::PLAYER,
+    physics_layers::PLAYER_DEAD,
+    physics_layers::BULLET,
+    physics_layers::PICKUP,
+    physics_layers::SPIKE_ROLLER,
+]);
+
+/// Check for bullets colliding with tiles and enemies and apply damage to them.
+pub fn handle_bullets(game: &mut sf::Game, assets: &Assets, camera: &sf::Camera) {
+    // gather hits first so that we don't need tricky nested query shenanigans.
+    // each bullet gets a list of everything it hit this frame, ordered by distance
+    let mut hits: Vec<(sf::hecs::Entity, Vec<sf::hecs::Entity>)> = Vec::new();
+    // also recycle bullets that have left the area visible on camera
+    let mut off_cameras: Vec<sf::hecs::Entity> = Vec::new();
+    for (bullet_ent, (bullet, pose)) in game.world.query_mut::<(&Bullet, &mut sf::Pose)>() {
+        if !bullet.active {
+            continue;
+        }
+
+        let hits_in_path = physics_query::spherecast_all(
+            &game.physics,
+            bullet.radius,
+            sf::Ray {
+                start: pose.translation.xy().to_precision(),
+                dir: bullet.dir,
+                // add a bit of extra distance to avoid tunneling
+                length: bullet.speed * game.dt_fixed + 0.05,
+            },
+            BULLET_HITS,
+        );
+        let hit_ents: Vec<sf::hecs::Entity> = hits_in_path
+            .into_iter()
+            .filter_map(|hit| game.hecs_sync.get_collider_entity(hit.collider))
+            // a pierced object is usually still in the way for a few frames after,
+            // don't count it multiple times
+            .filter(|ent| !bullet.pierced.contains(ent))
+            .collect();
+        if !hit_ents.is_empty() {
+            hits.push((bullet_ent, hit_ents));
+        }
+
+        let step = bullet.speed * game.dt_fixed * *bullet.dir;
+        pose.translation.x += step.x as f32;
+        pose.translation.y += step.y as f32;
+
+        if camera
+            .point_world_to_screen(pose.translation.xy())
+            .is_none()
+        {
+            off_cameras.push(bullet_ent);
+        }
+    }
+
+    for (bullet_ent, others) in hits {
+        let Ok((damage, dir)) = game
+            .world
+            .get::<&Bullet>(bullet_ent)
+            .map(|b| (b.damage, *b.dir))
+        else {
+            continue;
+        };
+        let mut stopped = false;
+
+        // damage everything in order until something stops the bullet.
+        // tiles that block bullets always stop them,
+        // piercing only goes through enemies and their projectiles
+        for other in others {
+            let hit_pierceable =
+                if let Ok((tile,)) = game.world.query_one_mut::<(&mut BreakableTile,)>(other) {
+                    tile.is_breaking = true;
+                    tile.time_to_break -= damage;
+                    if tile.blocks_bullets {
+                        stopped = true;
+                        break;
+                    }
+                    false
+                } else if let Ok(true) = game.world.satisfies::<(&Enemy,)>(other) {
+                    Enemy::hit(game, assets, other, EnemyHit::Bullet { damage, dir });
+                    true
+                } else if let Ok(true) = game.world.satisfies::<(&EnemyProjectile,)>(other) {
+                    // enemy projectiles can be shot down
+                    game.world.despawn(other).ok();
+                    true
+                } else {
+                    false
+                };
+
+            if !hit_pierceable {
+                continue;
+            }
+            let Ok(mut bullet) = game.world.get::<&mut Bullet>(bullet_ent) else {
+                break;
+            };
+            if bullet.pierce_left > 0 {
+                bullet.pierce_left -= 1;
+                bullet.pierced.push(other);
+            } else {
+                stopped = true;
+                break;
+            }
+        }
+
+        if stopped {
+            deactivate_bullet(game, bullet_ent);
+        }
+    }
+
+    for bullet in off_cameras {
+        deactivate_bullet(game, bullet);
+    }
+}